@@ -0,0 +1,247 @@
+use crate::{
+    comment_parser::CommentItem,
+    docgen::{BusRole, SvPort, SvPortGroup},
+};
+
+/// A standard bus protocol recognized from the names of its ports.
+struct Protocol {
+    name: &'static str,
+    /// Signal names following the prefix of the interface, e.g. `awvalid` in `s_axi_awvalid`.
+    signals: &'static [&'static str],
+    /// Signals an interface needs to be recognized, any of the alternatives.
+    required: &'static [&'static [&'static str]],
+    /// Signal driven by the master, telling the role of the interface.
+    driven: &'static [&'static str],
+}
+
+const AXI: &[&str] = &[
+    "aclk", "aresetn", "awid", "awaddr", "awlen", "awsize", "awburst", "awlock", "awcache",
+    "awprot", "awqos", "awregion", "awuser", "awvalid", "awready", "wid", "wdata", "wstrb",
+    "wlast", "wuser", "wvalid", "wready", "bid", "bresp", "buser", "bvalid", "bready", "arid",
+    "araddr", "arlen", "arsize", "arburst", "arlock", "arcache", "arprot", "arqos", "arregion",
+    "aruser", "arvalid", "arready", "rid", "rdata", "rresp", "rlast", "ruser", "rvalid", "rready",
+];
+
+/// Signals of AXI4 bursts, missing in AXI4-Lite.
+const AXI_BURST: &[&str] = &["awlen", "awburst", "wlast", "arlen", "arburst", "rlast"];
+
+const PROTOCOLS: &[Protocol] = &[
+    Protocol {
+        name: "AXI4",
+        signals: AXI,
+        required: &[&["awvalid", "awready"], &["arvalid", "arready"]],
+        driven: &["awvalid", "arvalid"],
+    },
+    Protocol {
+        name: "AXI-Stream",
+        signals: &[
+            "aclk", "aresetn", "tvalid", "tready", "tdata", "tstrb", "tkeep", "tlast", "tid",
+            "tdest", "tuser",
+        ],
+        required: &[&["tvalid"]],
+        driven: &["tvalid"],
+    },
+    Protocol {
+        name: "AHB",
+        signals: &[
+            "hclk",
+            "hresetn",
+            "haddr",
+            "htrans",
+            "hwrite",
+            "hsize",
+            "hburst",
+            "hprot",
+            "hmastlock",
+            "hsel",
+            "hwdata",
+            "hrdata",
+            "hready",
+            "hreadyout",
+            "hresp",
+        ],
+        required: &[&["haddr", "htrans"]],
+        driven: &["htrans"],
+    },
+    Protocol {
+        name: "APB",
+        signals: &[
+            "pclk", "presetn", "paddr", "psel", "penable", "pwrite", "pwdata", "pstrb", "pprot",
+            "prdata", "pready", "pslverr",
+        ],
+        required: &[&["psel", "penable"]],
+        driven: &["penable"],
+    },
+];
+
+/// Protocol signal named by `port` for a bus with the prefix `prefix`.
+fn signal(protocol: &Protocol, port: &str, prefix: &str) -> Option<&'static str> {
+    let name = port.to_lowercase();
+    let signal = name.strip_prefix(&prefix.to_lowercase())?;
+    protocol.signals.iter().find(|x| **x == signal).copied()
+}
+
+/// Role of the interface `ports` of `protocol`, by the direction of a signal the master drives.
+fn role(protocol: &Protocol, ports: &[&SvPort], prefix: &str) -> Option<BusRole> {
+    let port = ports.iter().find(|x| {
+        signal(protocol, &x.name, prefix).is_some_and(|x| protocol.driven.contains(&x))
+    })?;
+    match port.direction.as_deref() {
+        Some("output") => Some(BusRole::Master),
+        Some("input") => Some(BusRole::Slave),
+        _ => None,
+    }
+}
+
+/// Prefixes of the ports of `protocol` among `ports`, such as `s_axi_` or an empty one.
+fn prefixes(protocol: &Protocol, ports: &[&SvPort]) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for port in ports {
+        let name = port.name.to_lowercase();
+        for signal in protocol.signals {
+            let Some(prefix) = name.strip_suffix(signal) else {
+                continue;
+            };
+            // the prefix as written, names are ASCII
+            let prefix = &port.name[..prefix.len()];
+            if (prefix.is_empty() || prefix.ends_with('_')) && !result.iter().any(|x| x == prefix) {
+                result.push(prefix.to_string());
+            }
+        }
+    }
+    result
+}
+
+/// Interface of `protocol` with the prefix `prefix` among `ports`, if its required signals are present.
+fn detect(protocol: &Protocol, ports: &[&SvPort], prefix: &str) -> Option<SvPortGroup> {
+    let members: Vec<&SvPort> = ports
+        .iter()
+        .filter(|x| signal(protocol, &x.name, prefix).is_some())
+        .copied()
+        .collect();
+    let signals: Vec<&str> = members
+        .iter()
+        .filter_map(|x| signal(protocol, &x.name, prefix))
+        .collect();
+    let complete = protocol
+        .required
+        .iter()
+        .any(|x| x.iter().all(|x| signals.contains(x)));
+    if !complete {
+        return None;
+    }
+    let name = if protocol.signals == AXI && !signals.iter().any(|x| AXI_BURST.contains(x)) {
+        "AXI4-Lite"
+    } else {
+        protocol.name
+    };
+    let group = match prefix.trim_end_matches('_') {
+        "" => name.to_lowercase(),
+        x => x.to_string(),
+    };
+    Some(SvPortGroup {
+        name: group,
+        protocol: Some(name.to_string()),
+        role: role(protocol, &members, prefix),
+        desc: String::new(),
+        ports: members.iter().map(|x| x.name.clone()).collect(),
+    })
+}
+
+/// Port groups given by `@portgroup` in `items`, followed by the standard bus interfaces
+/// detected among the other ports, and `items` without the `@portgroup`s.
+pub fn port_groups(
+    items: &[CommentItem],
+    ports: &[SvPort],
+) -> (Vec<SvPortGroup>, Vec<CommentItem>) {
+    let mut result: Vec<SvPortGroup> = Vec::new();
+    let mut comment = Vec::new();
+    let grouped =
+        |groups: &[SvPortGroup], port: &SvPort| groups.iter().any(|x| x.ports.contains(&port.name));
+    for item in items {
+        let CommentItem::PortGroup { name, desc } = item else {
+            comment.push(item.clone());
+            continue;
+        };
+        let prefix = format!("{}_", name);
+        let members: Vec<&SvPort> = ports
+            .iter()
+            .filter(|x| x.name.starts_with(&prefix) && !grouped(&result, x))
+            .collect();
+        // the protocol of a declared group is still detected
+        let detected = PROTOCOLS.iter().find_map(|x| detect(x, &members, &prefix));
+        result.push(SvPortGroup {
+            name: name.clone(),
+            protocol: detected.as_ref().and_then(|x| x.protocol.clone()),
+            role: detected.and_then(|x| x.role),
+            desc: desc.trim().to_string(),
+            ports: members.iter().map(|x| x.name.clone()).collect(),
+        });
+    }
+    for protocol in PROTOCOLS {
+        let free: Vec<&SvPort> = ports.iter().filter(|x| !grouped(&result, x)).collect();
+        for prefix in prefixes(protocol, &free) {
+            let free: Vec<&SvPort> = free
+                .iter()
+                .filter(|x| !grouped(&result, x))
+                .copied()
+                .collect();
+            if let Some(group) = detect(protocol, &free, &prefix) {
+                result.push(group);
+            }
+        }
+    }
+    result.sort_by_key(|x| ports.iter().position(|p| x.ports.contains(&p.name)));
+    (result, comment)
+}
+
+#[test]
+fn test_port_groups() {
+    let port = |name: &str, direction: &str| SvPort {
+        name: name.to_string(),
+        port_type: None,
+        direction: Some(direction.to_string()),
+        dimensions: None,
+        comment: String::new(),
+        tags: Vec::new(),
+        lifecycle: Default::default(),
+        inherited: None,
+        clocking: Default::default(),
+        reqs: Vec::new(),
+        open: Vec::new(),
+    };
+    let ports = vec![
+        port("clk", "input"),
+        port("S_AXI_ARVALID", "input"),
+        port("S_AXI_ARREADY", "output"),
+        port("S_AXI_RLAST", "output"),
+        port("psel", "output"),
+        port("penable", "output"),
+        port("dbg_en", "output"),
+    ];
+    let items = vec![CommentItem::PortGroup {
+        name: "dbg".to_string(),
+        desc: " debug".to_string(),
+    }];
+    let (groups, comment) = port_groups(&items, &ports);
+    assert!(comment.is_empty());
+    let groups: Vec<_> = groups
+        .iter()
+        .map(|x| {
+            (
+                x.name.as_str(),
+                x.protocol.as_deref(),
+                x.role,
+                x.ports.len(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            ("S_AXI", Some("AXI4"), Some(BusRole::Slave), 3),
+            ("apb", Some("APB"), Some(BusRole::Master), 2),
+            ("dbg", None, None, 1),
+        ]
+    );
+}
@@ -1,8 +1,10 @@
+use crate::config::{CustomTag, TagShape};
 use nom::{
     branch::alt,
-    bytes::complete::{is_a, is_not, tag, take_until},
-    combinator::opt,
-    multi::many0,
+    bytes::complete::{is_a, is_not, tag, take, take_until},
+    combinator::{eof, map, not, opt, peek},
+    error::ErrorKind,
+    multi::{many0, many1, separated_list1},
     sequence::{delimited, terminated},
     AsChar, IResult, InputTakeAtPosition,
 };
@@ -12,16 +14,116 @@ pub(crate) const AZ_: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWX
 //pub(crate) const AZ09_: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_";
 pub(crate) const AZ09_DOLLAR: &str =
     "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_$";
+/// Characters of a name or of a glob pattern matching names.
+const PATTERN: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_$*?";
+pub(crate) const LANG: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ-_";
+
+type CommentItemParser<'a> = Box<dyn Fn(Span) -> IResult<Span, CommentItem> + 'a>;
+
+/// Kind of a command, with the custom tag and the documented name if any.
+type ItemKey<'a> = (
+    std::mem::Discriminant<CommentItem>,
+    Option<&'a str>,
+    Option<&'a str>,
+);
+
+/// Names of the built-in commands, without the `@`.
+pub const COMMANDS: &[&str] = &[
+    "brief",
+    "note",
+    "details",
+    "copydoc",
+    "copybrief",
+    "ref",
+    "see",
+    "code",
+    "endcode",
+    "example",
+    "endexample",
+    "image",
+    "dot",
+    "mermaid",
+    "plantuml",
+    "wave",
+    "bitfield",
+    "author",
+    "return",
+    "fsm",
+    "rev",
+    "port",
+    "param",
+    "state",
+    "reg",
+    "field",
+    "portgroup",
+    "clock",
+    "reset",
+    "domain",
+    "defgroup",
+    "addtogroup",
+    "ingroup",
+    "deprecated",
+    "since",
+    "version",
+    "req",
+    "todo",
+    "bug",
+    "fixme",
+];
+
+/// Kind of an open item left in the documentation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpenKind {
+    Todo,
+    Bug,
+    Fixme,
+}
+
+/// Renderer of a `@dot`, `@mermaid` or `@plantuml` diagram.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiagramKind {
+    Dot,
+    Mermaid,
+    PlantUml,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum CommentItem {
     Plain(String),
     Brief(String),
+    /// The long description, given by `@details` or split from the brief by autobrief.
+    Details(String),
+    /// `@copydoc target`, resolved after all files are parsed.
+    CopyDoc(String),
+    /// `@copybrief target`
+    CopyBrief(String),
     Note(String),
     Ref(String),
     See(String),
-    Example(String),
+    /// `@example`, with the file and line of the command for `--check-examples`.
+    Example {
+        lang: Option<String>,
+        code: String,
+        file: Option<String>,
+        line: usize,
+    },
+    Code {
+        lang: Option<String>,
+        code: String,
+    },
     Wave(String),
+    /// `@dot` ... `@enddot` and the like, kept verbatim.
+    Diagram {
+        kind: DiagramKind,
+        source: String,
+    },
+    /// `@image path "caption"`. Docgen makes a relative path relative to the documented source file.
+    Image {
+        path: String,
+        caption: Option<String>,
+    },
+    /// WaveDrom `reg` JSON of a bitfield diagram.
+    Bitfield(String),
     Author(String),
     Rev {
         name: String,
@@ -46,6 +148,90 @@ pub enum CommentItem {
         to: String,
         desc: String,
     },
+    Lang {
+        lang: String,
+        item: Box<CommentItem>,
+    },
+    Custom {
+        tag: String,
+        name: Option<String>,
+        desc: String,
+    },
+    /// `@deprecated reason`, or `@deprecated name: reason` for a port or param.
+    Deprecated {
+        name: Option<String>,
+        desc: String,
+    },
+    Since {
+        name: Option<String>,
+        version: String,
+    },
+    Version {
+        name: Option<String>,
+        version: String,
+    },
+    /// `@defgroup name Title`, the comment documents the group.
+    DefGroup {
+        name: String,
+        title: String,
+    },
+    /// `@addtogroup name [Title]`, like `@defgroup` for a group that may be defined elsewhere.
+    AddToGroup {
+        name: String,
+        title: String,
+    },
+    /// `@ingroup name...`
+    InGroup(String),
+    /// `@{`, the entities up to the matching `@}` belong to the group of this comment.
+    GroupOpen,
+    GroupClose,
+    /// `@reg NAME offset=0x10 access=RW reset=0x0: desc`
+    Reg {
+        name: String,
+        attrs: Vec<(String, String)>,
+        desc: String,
+    },
+    /// `@field name[7:4] access=RO: desc`, a field of the preceding `@reg`.
+    Field {
+        name: String,
+        bits: String,
+        attrs: Vec<(String, String)>,
+        desc: String,
+    },
+    /// `@portgroup name: desc`, the ports named `name_*` form a bus interface.
+    PortGroup {
+        name: String,
+        desc: String,
+    },
+    /// `@clock desc` on a port, or `@clock names: desc` naming ports.
+    Clock {
+        name: Option<String>,
+        desc: String,
+    },
+    /// `@reset [active-low|active-high] [async|sync] desc`, on a port or naming ports.
+    Reset {
+        name: Option<String>,
+        desc: String,
+    },
+    /// `@domain clock` on a port, or `@domain names: clock` naming ports.
+    Domain {
+        name: Option<String>,
+        clock: String,
+    },
+    /// `@req ID, ...` on the documented entity, or `@req name: ID, ...` naming ports or a
+    /// state transition `from->to`.
+    Req {
+        name: Option<String>,
+        ids: String,
+    },
+    /// `@todo`, `@bug` or `@fixme`. The line is counted from the start of the comment
+    /// until docgen locates it in the source file.
+    Open {
+        kind: OpenKind,
+        text: String,
+        file: Option<String>,
+        line: usize,
+    },
 }
 
 impl CommentItem {
@@ -54,10 +240,16 @@ impl CommentItem {
             CommentItem::Plain(x) => x.push_str(s),
             CommentItem::Brief(x) => x.push_str(s),
             CommentItem::Note(x) => x.push_str(s),
+            CommentItem::Details(x) => x.push_str(s),
+            CommentItem::CopyDoc(x) => x.push_str(s),
+            CommentItem::CopyBrief(x) => x.push_str(s),
             CommentItem::Ref(x) => x.push_str(s),
             CommentItem::See(x) => x.push_str(s),
-            CommentItem::Example(x) => x.push_str(s),
+            CommentItem::Example { code, .. } => code.push_str(s),
+            CommentItem::Code { lang: _, code } => code.push_str(s),
+            CommentItem::Diagram { kind: _, source } => source.push_str(s),
             CommentItem::Wave(x) => x.push_str(s),
+            CommentItem::Bitfield(x) => x.push_str(s),
             CommentItem::Author(x) => x.push_str(s),
             CommentItem::Rev { name: _, desc } => desc.push_str(s),
             CommentItem::Port { name: _, desc } => desc.push_str(s),
@@ -70,8 +262,57 @@ impl CommentItem {
                 to: _,
                 desc,
             } => desc.push_str(s),
+            CommentItem::Lang { lang: _, item } => item.append_str(s),
+            CommentItem::Custom {
+                tag: _,
+                name: _,
+                desc,
+            } => desc.push_str(s),
+            CommentItem::Deprecated { name: _, desc } => desc.push_str(s),
+            CommentItem::Since { name: _, version } => version.push_str(s),
+            CommentItem::Version { name: _, version } => version.push_str(s),
+            CommentItem::Open { text, .. } => text.push_str(s),
+            CommentItem::DefGroup { name: _, title } => title.push_str(s),
+            CommentItem::AddToGroup { name: _, title } => title.push_str(s),
+            CommentItem::InGroup(x) => x.push_str(s),
+            CommentItem::Reg { desc, .. } => desc.push_str(s),
+            CommentItem::Field { desc, .. } => desc.push_str(s),
+            CommentItem::PortGroup { desc, .. } => desc.push_str(s),
+            CommentItem::Clock { desc, .. } => desc.push_str(s),
+            CommentItem::Reset { desc, .. } => desc.push_str(s),
+            CommentItem::Domain { .. } => (),
+            CommentItem::Req { .. } => (),
+            CommentItem::Image { .. } => (),
+            CommentItem::GroupOpen => (),
+            CommentItem::GroupClose => (),
         }
     }
+
+    /// Commands with the same key are variants of each other, e.g. `@port a` and `@port[zh] a`.
+    fn key(&self) -> ItemKey<'_> {
+        let (tag, name) = match self {
+            CommentItem::Rev { name, desc: _ } => (None, Some(name.as_str())),
+            CommentItem::Port { name, desc: _ } => (None, Some(name.as_str())),
+            CommentItem::Param { name, desc: _ } => (None, Some(name.as_str())),
+            CommentItem::State { name, desc: _ } => (None, Some(name.as_str())),
+            CommentItem::Custom { tag, name, desc: _ } => (Some(tag.as_str()), name.as_deref()),
+            CommentItem::Deprecated { name, desc: _ } => (None, name.as_deref()),
+            CommentItem::Since { name, version: _ } => (None, name.as_deref()),
+            CommentItem::Version { name, version: _ } => (None, name.as_deref()),
+            CommentItem::DefGroup { name, title: _ } => (None, Some(name.as_str())),
+            CommentItem::Reg { name, .. } => (None, Some(name.as_str())),
+            CommentItem::Field { name, .. } => (None, Some(name.as_str())),
+            CommentItem::PortGroup { name, .. } => (None, Some(name.as_str())),
+            CommentItem::Clock { name, .. } => (None, name.as_deref()),
+            CommentItem::Reset { name, .. } => (None, name.as_deref()),
+            CommentItem::Domain { name, .. } => (None, name.as_deref()),
+            CommentItem::Req { name, .. } => (None, name.as_deref()),
+            CommentItem::AddToGroup { name, title: _ } => (None, Some(name.as_str())),
+            CommentItem::Lang { lang: _, item } => return item.key(),
+            _ => (None, None),
+        };
+        (std::mem::discriminant(self), tag, name)
+    }
 }
 
 pub fn whitespace<T>(input: T) -> IResult<T, T>
@@ -85,7 +326,13 @@ where
     })
 }
 fn parse_comment_item_plain(s: Span) -> IResult<Span, CommentItem> {
-    let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+    // strip the `*` decoration and one space, but keep Markdown bullets and indentation
+    let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+    let (s, _) = opt(terminated(
+        is_a("*"),
+        peek(alt((tag(" "), tag("\t"), tag("\n"), eof))),
+    ))(s)?;
+    let (s, _) = opt(alt((tag(" "), tag("\t"))))(s)?;
     let (s, plain) = is_not("\n")(s)?;
     let (s, _) = opt(tag("\n"))(s)?;
     let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
@@ -101,21 +348,193 @@ fn parse_comment_item_empty(s: Span) -> IResult<Span, CommentItem> {
     Ok((s, CommentItem::Plain("".to_string())))
 }
 
+fn language_tag(s: Span) -> IResult<Span, String> {
+    let (s, lang) = delimited(tag("["), is_a(LANG), tag("]"))(s)?;
+    Ok((s, lang.to_string()))
+}
+
+fn with_language(lang: Option<String>, item: CommentItem) -> CommentItem {
+    match lang {
+        Some(lang) => CommentItem::Lang {
+            lang,
+            item: Box::new(item),
+        },
+        None => item,
+    }
+}
+
 fn parse_command_item_simple<'a>(
     cmd: &'a str,
     p: impl Fn(String) -> CommentItem + 'a,
-) -> Box<dyn Fn(Span) -> IResult<Span, CommentItem> + 'a> {
+) -> CommentItemParser<'a> {
     Box::new(move |s: Span| {
         let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
-        let (s, _) = (terminated(tag(cmd), alt((tag(" "), tag(":"), tag("\t")))))(s)?;
+        let (s, _) = tag(cmd)(s)?;
+        let (s, lang) = opt(language_tag)(s)?;
+        let (s, _) = alt((tag(" "), tag(":"), tag("\t")))(s)?;
         let (s, text) = opt(is_not("\n"))(s)?;
         let (s, _) = opt(tag("\n"))(s)?;
         let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
         let comment_line = text.unwrap_or(Span::from(""));
-        Ok((s, p(comment_line.to_string())))
+        Ok((s, with_language(lang, p(comment_line.to_string()))))
     })
 }
 
+/// Removes the `*` decoration and the common indentation of verbatim lines,
+/// as well as leading and trailing empty lines.
+fn verbatim(lines: &[&str]) -> String {
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|x| {
+            let x = x.trim_end();
+            match x.trim_start().strip_prefix('*') {
+                Some(y) if !y.starts_with('/') => y,
+                _ => x,
+            }
+        })
+        .collect();
+    let indent = lines
+        .iter()
+        .filter(|x| !x.trim().is_empty())
+        .map(|x| x.len() - x.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|x| x.get(indent..).unwrap_or(""))
+        .skip_while(|x| x.is_empty())
+        .collect();
+    let end = lines
+        .iter()
+        .rposition(|x| !x.is_empty())
+        .map_or(0, |x| x + 1);
+    lines[..end].join("\n")
+}
+
+fn code_language(s: Span) -> IResult<Span, String> {
+    let (s, _) = tag("{")(s)?;
+    let (s, _) = opt(tag("."))(s)?;
+    let (s, lang) = terminated(is_not("}\n"), tag("}"))(s)?;
+    Ok((s, lang.trim().to_string()))
+}
+
+/// `@code{.lang}` ... `@endcode`, kept verbatim.
+fn parse_command_item_code(s: Span) -> IResult<Span, CommentItem> {
+    let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+    let (s, _) = tag("@code")(s)?;
+    let (s, lang) = opt(code_language)(s)?;
+    let (s, code) = verbatim_block("@endcode")(s)?;
+    Ok((s, CommentItem::Code { lang, code }))
+}
+
+/// Verbatim lines up to the `end` command, starting with the rest of the current line.
+fn verbatim_block<'a>(end: &'a str) -> impl Fn(Span) -> IResult<Span, String> + 'a {
+    move |s| {
+        let (s, first) = opt(is_not("\n"))(s)?;
+        let (s, _) = opt(tag("\n"))(s)?;
+        let (s, body) = take_until(end)(s)?;
+        let (s, _) = tag(end)(s)?;
+        let (s, _) = opt(is_not("\n"))(s)?;
+        let (s, _) = opt(tag("\n"))(s)?;
+        let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+
+        // the decoration in front of the end command is not part of the block
+        let body = body.fragment();
+        let body = &body[..body.rfind('\n').map_or(0, |x| x + 1)];
+        let mut lines: Vec<&str> = first.iter().map(|x| *x.fragment()).collect();
+        lines.extend(body.lines());
+        Ok((s, verbatim(&lines)))
+    }
+}
+
+/// `@image path` or `@image path "caption"`.
+fn parse_command_item_image(s: Span) -> IResult<Span, CommentItem> {
+    let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+    let (s, _) = tag("@image")(s)?;
+    let (s, _) = is_a(" \t")(s)?;
+    let (s, path) = is_not(" \t\n")(s)?;
+    let (s, caption) = opt(is_not("\n"))(s)?;
+    let (s, _) = opt(tag("\n"))(s)?;
+    let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+    let caption = caption
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .map(|x| enquote::unquote(x).unwrap_or_else(|_| x.to_string()));
+    Ok((
+        s,
+        CommentItem::Image {
+            path: path.to_string(),
+            caption,
+        },
+    ))
+}
+
+/// `@dot` ... `@enddot`, `@mermaid` ... `@endmermaid` or `@plantuml` ... `@endplantuml`.
+fn parse_command_item_diagram(s: Span) -> IResult<Span, CommentItem> {
+    let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+    let (s, (kind, end)) = alt((
+        map(tag("@dot"), |_| (DiagramKind::Dot, "@enddot")),
+        map(tag("@mermaid"), |_| (DiagramKind::Mermaid, "@endmermaid")),
+        map(tag("@plantuml"), |_| {
+            (DiagramKind::PlantUml, "@endplantuml")
+        }),
+    ))(s)?;
+    let (s, _) = peek(alt((tag(" "), tag("\t"), tag("\n"))))(s)?;
+    let (s, source) = verbatim_block(end)(s)?;
+    Ok((s, CommentItem::Diagram { kind, source }))
+}
+
+/// Whether the comment line `text` starts with a command rather than SystemVerilog such as
+/// `@(posedge clk)`.
+fn is_command(text: &str) -> bool {
+    let Some(rest) = text.strip_prefix('@') else {
+        return false;
+    };
+    let word: String = rest
+        .chars()
+        .take_while(|x| x.is_ascii_alphanumeric() || *x == '_')
+        .collect();
+    rest.starts_with(['{', '}'])
+        || COMMANDS.contains(&word.as_str())
+        || parse_command_item_transit(Span::from(text)).is_ok()
+}
+
+/// `@example{.lang}`, followed by verbatim lines until `@endexample`, the next command or the
+/// end of the comment.
+fn parse_command_item_example(s: Span) -> IResult<Span, CommentItem> {
+    let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+    let line = s.location_line() as usize;
+    let (s, _) = tag("@example")(s)?;
+    let (s, lang) = opt(language_tag)(s)?;
+    let (s, code_lang) = opt(code_language)(s)?;
+    let (s, _) = alt((tag(" "), tag(":"), tag("\t"), tag("\n"), eof))(s)?;
+    let (s, first) = opt(is_not("\n"))(s)?;
+    let (s, _) = opt(tag("\n"))(s)?;
+
+    let mut lines: Vec<&str> = first.iter().map(|x| *x.fragment()).collect();
+    let mut len = 0;
+    for line in s.fragment().split_inclusive('\n') {
+        let command = line.trim_start_matches([' ', '\t', '*']);
+        if command.trim_end() == "@endexample" {
+            len += line.len();
+            break;
+        }
+        if is_command(command) {
+            break;
+        }
+        lines.push(line.trim_end_matches('\n'));
+        len += line.len();
+    }
+    let (s, _) = take(len)(s)?;
+    let item = CommentItem::Example {
+        lang: Some(code_lang.unwrap_or_else(|| "systemverilog".to_string())),
+        code: verbatim(&lines),
+        file: None,
+        line,
+    };
+    Ok((s, with_language(lang, item)))
+}
+
 fn identifier(s: Span) -> IResult<Span, String> {
     let (s, a) = is_a(AZ_)(s)?;
     let (s, b) = opt(is_a(AZ09_DOLLAR))(s)?;
@@ -127,22 +546,170 @@ fn identifier(s: Span) -> IResult<Span, String> {
     Ok((s, a))
 }
 
+/// Comma-separated names or glob patterns such as `a, b` or `s_axi_*`, joined by `,`.
+fn name_patterns(s: Span) -> IResult<Span, String> {
+    let separator = delimited(many0(tag(" ")), tag(","), many0(tag(" ")));
+    let (s, names) = separated_list1(separator, is_a(PATTERN))(s)?;
+    let names: Vec<&str> = names.iter().map(|x| *x.fragment()).collect();
+    Ok((s, names.join(",")))
+}
+
+/// Ports or params as for `@port`, or a state transition written `from -> to`.
+fn req_target(s: Span) -> IResult<Span, String> {
+    let transition = |s| {
+        let (s, from) = terminated(identifier, delimited(whitespace, tag("->"), whitespace))(s)?;
+        let (s, to) = identifier(s)?;
+        Ok((s, format!("{}->{}", from, to)))
+    };
+    alt((transition, name_patterns))(s)
+}
+
 fn parse_command_item_pair<'a>(
     cmd: &'a str,
+    name: fn(Span) -> IResult<Span, String>,
     p: impl Fn(String, String) -> CommentItem + 'a,
-) -> Box<dyn Fn(Span) -> IResult<Span, CommentItem> + 'a> {
+) -> CommentItemParser<'a> {
     Box::new(move |s: Span| {
         let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
-        let (s, _) = (terminated(tag(cmd), tag(" ")))(s)?;
-        let (s, name) = terminated(identifier, alt((tag(" "), tag(":"))))(s)?;
+        let (s, _) = tag(cmd)(s)?;
+        let (s, lang) = opt(language_tag)(s)?;
+        let (s, _) = tag(" ")(s)?;
+        let (s, name) = terminated(name, alt((tag(" "), tag(":"))))(s)?;
         let (s, desc) = opt(is_not("\n"))(s)?;
         let (s, _) = opt(tag("\n"))(s)?;
         let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
         let desc = desc.unwrap_or(Span::from(""));
-        Ok((s, p(name, desc.to_string())))
+        Ok((s, with_language(lang, p(name, desc.to_string()))))
+    })
+}
+
+/// `@cmd text` about the documented entity, or `@cmd name: text` about one of its ports or params.
+fn parse_command_item_named<'a>(
+    cmd: &'a str,
+    name: fn(Span) -> IResult<Span, String>,
+    p: impl Fn(Option<String>, String) -> CommentItem + 'a,
+) -> CommentItemParser<'a> {
+    Box::new(move |s: Span| {
+        let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+        let (s, _) = tag(cmd)(s)?;
+        let (s, lang) = opt(language_tag)(s)?;
+        let (s, _) = peek(alt((tag(" "), tag(":"), tag("\t"), tag("\n"), eof)))(s)?;
+        let (s, _) = many0(alt((tag(" "), tag(":"), tag("\t"))))(s)?;
+        let (s, name) = opt(terminated(name, tag(":")))(s)?;
+        let (s, text) = opt(is_not("\n"))(s)?;
+        let (s, _) = opt(tag("\n"))(s)?;
+        let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+        let text = text.map(|x| x.trim().to_string()).unwrap_or_default();
+        Ok((s, with_language(lang, p(name, text))))
+    })
+}
+
+/// `@todo`, `@bug` or `@fixme`, remembering the line of the comment it is on.
+fn parse_command_item_open<'a>(cmd: &'a str, kind: OpenKind) -> CommentItemParser<'a> {
+    Box::new(move |s: Span| {
+        let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+        let line = s.location_line() as usize;
+        let (s, _) = tag(cmd)(s)?;
+        let (s, lang) = opt(language_tag)(s)?;
+        let (s, _) = peek(alt((tag(" "), tag(":"), tag("\t"), tag("\n"), eof)))(s)?;
+        let (s, _) = many0(alt((tag(" "), tag(":"), tag("\t"))))(s)?;
+        let (s, text) = opt(is_not("\n"))(s)?;
+        let (s, _) = opt(tag("\n"))(s)?;
+        let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+        let item = CommentItem::Open {
+            kind,
+            text: text.map(|x| x.trim().to_string()).unwrap_or_default(),
+            file: None,
+            line,
+        };
+        Ok((s, with_language(lang, item)))
     })
 }
 
+/// ` key=value` attributes of `@reg` and `@field`.
+fn attributes(s: Span) -> IResult<Span, Vec<(String, String)>> {
+    many0(|s| {
+        let (s, _) = is_a(" \t")(s)?;
+        let (s, key) = identifier(s)?;
+        let (s, _) = tag("=")(s)?;
+        let (s, value) = is_not(" \t\n:")(s)?;
+        Ok((s, (key, value.to_string())))
+    })(s)
+}
+
+/// The description after the attributes, separated by `:`.
+fn attribute_desc(s: Span) -> IResult<Span, String> {
+    let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+    let (s, _) = opt(tag(":"))(s)?;
+    let (s, desc) = opt(is_not("\n"))(s)?;
+    let (s, _) = opt(tag("\n"))(s)?;
+    let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+    Ok((s, desc.map(|x| x.trim().to_string()).unwrap_or_default()))
+}
+
+fn parse_command_item_reg(s: Span) -> IResult<Span, CommentItem> {
+    let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+    let (s, _) = tag("@reg")(s)?;
+    let (s, _) = is_a(" \t")(s)?;
+    let (s, name) = identifier(s)?;
+    let (s, attrs) = attributes(s)?;
+    let (s, desc) = attribute_desc(s)?;
+    Ok((s, CommentItem::Reg { name, attrs, desc }))
+}
+
+fn parse_command_item_field(s: Span) -> IResult<Span, CommentItem> {
+    let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+    let (s, _) = tag("@field")(s)?;
+    let (s, _) = is_a(" \t")(s)?;
+    let (s, name) = identifier(s)?;
+    let (s, bits) = opt(delimited(tag("["), is_not("]\n"), tag("]")))(s)?;
+    let (s, attrs) = attributes(s)?;
+    let (s, desc) = attribute_desc(s)?;
+    Ok((
+        s,
+        CommentItem::Field {
+            name,
+            bits: bits.map(|x| x.trim().to_string()).unwrap_or_default(),
+            attrs,
+            desc,
+        },
+    ))
+}
+
+/// `@defgroup name Title` or `@addtogroup name [Title]`.
+fn parse_command_item_group<'a>(
+    cmd: &'a str,
+    p: impl Fn(String, String) -> CommentItem + 'a,
+) -> CommentItemParser<'a> {
+    Box::new(move |s: Span| {
+        let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+        let (s, _) = tag(cmd)(s)?;
+        let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+        let (s, name) = identifier(s)?;
+        let (s, _) = many0(alt((tag(" "), tag(":"), tag("\t"))))(s)?;
+        let (s, title) = opt(is_not("\n"))(s)?;
+        let (s, _) = opt(tag("\n"))(s)?;
+        let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+        let title = title.map(|x| x.trim().to_string()).unwrap_or_default();
+        Ok((s, p(name, title)))
+    })
+}
+
+/// `@{` or `@}` on its own line.
+fn parse_command_item_group_scope(s: Span) -> IResult<Span, CommentItem> {
+    let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+    let (s, item) = alt((tag("@{"), tag("@}")))(s)?;
+    let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+    let (s, _) = alt((tag("\n"), eof))(s)?;
+    let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+    let item = if *item.fragment() == "@{" {
+        CommentItem::GroupOpen
+    } else {
+        CommentItem::GroupClose
+    };
+    Ok((s, item))
+}
+
 fn parse_command_item_transit(s: Span) -> IResult<Span, CommentItem> {
     let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
     let (s, _) = tag("@")(s)?;
@@ -161,73 +728,369 @@ fn parse_command_item_transit(s: Span) -> IResult<Span, CommentItem> {
     ))
 }
 
-fn comment_item(s: Span) -> IResult<Span, CommentItem> {
+/// `@tag text` or `@tag name: text` for a tag declared in the configuration.
+fn parse_command_item_custom<'a>(tags: &'a [CustomTag]) -> CommentItemParser<'a> {
+    Box::new(move |s: Span| {
+        let (s, _) = many0(alt((tag(" "), tag("*"), tag("\t"))))(s)?;
+        let (s, _) = tag("@")(s)?;
+        let (s, cmd) = identifier(s)?;
+        let custom = match tags.iter().find(|x| x.name == cmd) {
+            Some(x) => x,
+            None => return Err(nom::Err::Error(nom::error::Error::new(s, ErrorKind::Tag))),
+        };
+        let (s, lang) = opt(language_tag)(s)?;
+        let (s, name) = if custom.shape == TagShape::Pair {
+            let (s, _) = tag(" ")(s)?;
+            let (s, name) = terminated(name_patterns, alt((tag(" "), tag(":"))))(s)?;
+            (s, Some(name))
+        } else {
+            let (s, _) = alt((tag(" "), tag(":"), tag("\t"), tag("\n"), eof))(s)?;
+            (s, None)
+        };
+        let (s, desc) = opt(is_not("\n"))(s)?;
+        let (s, _) = opt(tag("\n"))(s)?;
+        let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+        let item = CommentItem::Custom {
+            tag: cmd,
+            name,
+            desc: desc.map(|x| x.trim().to_string()).unwrap_or_default(),
+        };
+        Ok((s, with_language(lang, item)))
+    })
+}
+
+fn comment_item<'a>(tags: &'a [CustomTag]) -> impl Fn(Span) -> IResult<Span, CommentItem> + 'a {
+    move |s: Span| {
+        alt((
+            documentation_command,
+            tracking_command,
+            grouping_command,
+            parse_command_item_reg,
+            parse_command_item_field,
+            parse_command_item_transit,
+            parse_command_item_custom(tags),
+            interface_command,
+            parse_comment_item_plain,
+            parse_comment_item_empty,
+        ))(s)
+    }
+}
+
+/// Commands documenting the entity, its ports, params and state machines.
+fn documentation_command(s: Span) -> IResult<Span, CommentItem> {
+    alt((
+        parse_command_item_simple("@brief", CommentItem::Brief),
+        parse_command_item_simple("@note", CommentItem::Note),
+        parse_command_item_simple("@details", CommentItem::Details),
+        parse_command_item_simple("@copydoc", CommentItem::CopyDoc),
+        parse_command_item_simple("@copybrief", CommentItem::CopyBrief),
+        parse_command_item_simple("@ref", CommentItem::Ref),
+        parse_command_item_simple("@see", CommentItem::See),
+        parse_command_item_code,
+        parse_command_item_example,
+        parse_command_item_diagram,
+        parse_command_item_image,
+        parse_command_item_simple("@wave", CommentItem::Wave),
+        parse_command_item_simple("@bitfield", CommentItem::Bitfield),
+        parse_command_item_simple("@author", CommentItem::Author),
+        parse_command_item_simple("@return", CommentItem::Return),
+        parse_command_item_simple("@fsm", CommentItem::FSM),
+        parse_command_item_pair("@rev", identifier, |x, y| CommentItem::Rev {
+            name: x,
+            desc: y,
+        }),
+        parse_command_item_pair("@port", name_patterns, |x, y| CommentItem::Port {
+            name: x,
+            desc: y,
+        }),
+        parse_command_item_pair("@param", name_patterns, |x, y| CommentItem::Param {
+            name: x,
+            desc: y,
+        }),
+        parse_command_item_pair("@state", identifier, |x, y| CommentItem::State {
+            name: x,
+            desc: y,
+        }),
+    ))(s)
+}
+
+/// Commands describing the interface of a module: port groups, clocks and resets.
+fn interface_command(s: Span) -> IResult<Span, CommentItem> {
+    alt((
+        parse_command_item_pair("@portgroup", identifier, |x, y| CommentItem::PortGroup {
+            name: x,
+            desc: y,
+        }),
+        parse_command_item_named("@clock", name_patterns, |x, y| CommentItem::Clock {
+            name: x,
+            desc: y,
+        }),
+        parse_command_item_named("@reset", name_patterns, |x, y| CommentItem::Reset {
+            name: x,
+            desc: y,
+        }),
+        parse_command_item_named("@domain", name_patterns, |x, y| CommentItem::Domain {
+            name: x,
+            clock: y,
+        }),
+    ))(s)
+}
+
+/// Commands organizing entities into groups.
+fn grouping_command(s: Span) -> IResult<Span, CommentItem> {
     alt((
-        parse_command_item_simple("@brief", |x| CommentItem::Brief(x)),
-        parse_command_item_simple("@note", |x| CommentItem::Note(x)),
-        parse_command_item_simple("@ref", |x| CommentItem::Ref(x)),
-        parse_command_item_simple("@see", |x| CommentItem::See(x)),
-        parse_command_item_simple("@example", |x| CommentItem::Example(x)),
-        parse_command_item_simple("@wave", |x| CommentItem::Wave(x)),
-        parse_command_item_simple("@author", |x| CommentItem::Author(x)),
-        parse_command_item_simple("@return", |x| CommentItem::Return(x)),
-        parse_command_item_simple("@fsm", |x| CommentItem::FSM(x)),
-        parse_command_item_pair("@rev", |x, y| CommentItem::Rev { name: x, desc: y }),
-        parse_command_item_pair("@port", |x, y| CommentItem::Port { name: x, desc: y }),
-        parse_command_item_pair("@param", |x, y| CommentItem::Param { name: x, desc: y }),
-        parse_command_item_pair("@state", |x, y| CommentItem::State { name: x, desc: y }),
-        parse_command_item_transit,
-        parse_comment_item_plain,
-        parse_comment_item_empty,
+        parse_command_item_group("@defgroup", |x, y| CommentItem::DefGroup {
+            name: x,
+            title: y,
+        }),
+        parse_command_item_group("@addtogroup", |x, y| CommentItem::AddToGroup {
+            name: x,
+            title: y,
+        }),
+        parse_command_item_simple("@ingroup", CommentItem::InGroup),
+        parse_command_item_group_scope,
     ))(s)
 }
 
-fn oneline_comment(s: Span) -> IResult<Span, Vec<CommentItem>> {
-    let (s, _) = tag("//*")(s)?;
-    let (s, item) = comment_item(s)?;
-    Ok((s, vec![item]))
+/// Commands tracking versions, requirements and open items.
+fn tracking_command(s: Span) -> IResult<Span, CommentItem> {
+    alt((
+        parse_command_item_named("@deprecated", name_patterns, |x, y| {
+            CommentItem::Deprecated { name: x, desc: y }
+        }),
+        parse_command_item_named("@since", name_patterns, |x, y| CommentItem::Since {
+            name: x,
+            version: y,
+        }),
+        parse_command_item_named("@version", name_patterns, |x, y| CommentItem::Version {
+            name: x,
+            version: y,
+        }),
+        parse_command_item_named("@req", req_target, |x, y| CommentItem::Req {
+            name: x,
+            ids: y,
+        }),
+        parse_command_item_open("@todo", OpenKind::Todo),
+        parse_command_item_open("@bug", OpenKind::Bug),
+        parse_command_item_open("@fixme", OpenKind::Fixme),
+    ))(s)
+}
+
+/// Text of a `//*`, `///` or `//!` line.
+fn line_comment_line<'a>(s: Span<'a>) -> IResult<Span<'a>, &'a str> {
+    let (s, _) = many0(alt((tag(" "), tag("\t"))))(s)?;
+    let (s, _) = alt((
+        tag("//*"),
+        terminated(tag("///"), not(tag("/"))),
+        tag("//!"),
+    ))(s)?;
+    let (s, _) = opt(tag("<"))(s)?;
+    let (s, line) = opt(is_not("\n"))(s)?;
+    let (s, _) = opt(tag("\n"))(s)?;
+    Ok((s, line.map(|x| *x.fragment()).unwrap_or_default()))
+}
+
+/// Text of `//*`, `///` or `//!` lines, possibly merged into a block by docgen.
+fn line_comment(s: Span) -> IResult<Span, String> {
+    let (s, lines) = many1(line_comment_line)(s)?;
+    Ok((s, lines.join("\n")))
 }
 
-fn multiline_comment(s: Span) -> IResult<Span, Vec<CommentItem>> {
-    let (s, _) = tag("/**")(s)?;
+/// Text of `/** ... */` or `/*! ... */`.
+fn multiline_comment(s: Span) -> IResult<Span, String> {
+    let (s, _) = alt((tag("/**"), tag("/*!")))(s)?;
+    let (s, _) = opt(tag("<"))(s)?;
     let (s, comment) = take_until("*/")(s)?;
     let (s, _) = tag("*/")(s)?;
-    let (_, items) = many0(comment_item)(comment)?;
-    Ok((s, items))
+    Ok((s, comment.to_string()))
+}
+
+/// Text of a documentation comment without its delimiters, keeping its lines.
+fn comment_text(comment_str: &str) -> Option<String> {
+    alt((line_comment, multiline_comment))(Span::from(comment_str))
+        .ok()
+        .map(|x| x.1)
+}
+
+/// Whether `comment` is a `///<`, `//*<`, `/**<` or like comment, which documents the
+/// declaration preceding it rather than the following one.
+pub fn is_trailing_comment(comment: &str) -> bool {
+    ["//*<", "///<", "//!<", "/**<", "/*!<"]
+        .iter()
+        .any(|x| comment.starts_with(x))
+}
+
+/// Whether `comment` is a `///` or `//!` line, which merges with the adjacent ones.
+/// `////` banners are not documentation.
+pub fn is_line_comment(comment: &str) -> bool {
+    (comment.starts_with("///") && !comment.starts_with("////")) || comment.starts_with("//!")
 }
 
-fn post_process(v: Vec<CommentItem>) -> Vec<CommentItem> {
+/// The first sentence or paragraph of `text`, and the rest if any.
+fn split_first_sentence(text: &str) -> (String, Option<String>) {
+    let mut end = text.find("\n\n").unwrap_or(text.len());
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if i >= end {
+            break;
+        }
+        let next = chars.peek().map(|x| x.1);
+        if c == '。' || (c == '.' && next.is_none_or(char::is_whitespace)) {
+            end = i + c.len_utf8();
+            break;
+        }
+    }
+    let rest = text[end..].trim();
+    (
+        text[..end].trim_end().to_string(),
+        Some(rest.to_string()).filter(|x| !x.is_empty()),
+    )
+}
+
+fn post_process(v: Vec<CommentItem>, autobrief: bool) -> Vec<CommentItem> {
     let mut result: Vec<CommentItem> = vec![];
+    let mut paragraph_break = false;
+    // the brief taken from plain text rather than given by `@brief`
+    let mut implicit_brief = None;
 
     for item in v {
         match item {
             CommentItem::Plain(x) => {
-                if x != "" {
-                    if let Some(last) = result.last_mut() {
-                        last.append_str("\n");
+                if x.trim().is_empty() {
+                    paragraph_break = !result.is_empty();
+                } else {
+                    let separator = if paragraph_break { "\n\n" } else { "\n" };
+                    paragraph_break = false;
+                    let closed = matches!(
+                        result.last(),
+                        Some(CommentItem::Code { .. })
+                            | Some(CommentItem::Diagram { .. })
+                            | Some(CommentItem::Image { .. })
+                            | Some(CommentItem::Example { .. })
+                            | Some(CommentItem::DefGroup { .. })
+                            | Some(CommentItem::AddToGroup { .. })
+                    );
+                    if closed {
+                        // text after a verbatim block or a group title continues the brief
+                        if let Some(CommentItem::Brief(brief)) = result
+                            .iter_mut()
+                            .find(|x| matches!(x, CommentItem::Brief(_)))
+                        {
+                            brief.push_str(separator);
+                            brief.push_str(x.as_str());
+                        } else {
+                            implicit_brief = Some(result.len());
+                            result.push(CommentItem::Brief(x));
+                        }
+                    } else if let Some(last) = result.last_mut() {
+                        last.append_str(separator);
                         last.append_str(x.as_str());
                     } else {
+                        implicit_brief = Some(result.len());
                         result.push(CommentItem::Brief(x));
                     }
                 }
             }
             _ => {
+                paragraph_break = false;
                 result.push(item);
             }
         }
     }
 
+    if let Some(i) = implicit_brief.filter(|_| autobrief) {
+        if let CommentItem::Brief(text) = &result[i] {
+            let (brief, details) = split_first_sentence(text);
+            result[i] = CommentItem::Brief(brief);
+            if let Some(details) = details {
+                result.insert(i + 1, CommentItem::Details(details));
+            }
+        }
+    }
+    result
+}
+
+/// Keeps the `@cmd[lang]` variants matching `lang` and drops other languages.
+/// Untagged commands are kept unless a variant in `lang` replaces them: the n-th variant of a
+/// key replaces the n-th untagged command with that key, so that one `@note[zh]` replaces one
+/// `@note`.
+pub fn select_language(items: Vec<CommentItem>, lang: Option<&str>) -> Vec<CommentItem> {
+    let mut localized: Vec<(ItemKey, usize)> = Vec::new();
+    for item in &items {
+        if matches!(item, CommentItem::Lang { lang: l, .. } if Some(l.as_str()) == lang) {
+            match localized.iter_mut().find(|x| x.0 == item.key()) {
+                Some(x) => x.1 += 1,
+                None => localized.push((item.key(), 1)),
+            }
+        }
+    }
+
+    let mut result = Vec::new();
+    for item in &items {
+        match item {
+            CommentItem::Lang { lang: l, item } => {
+                if Some(l.as_str()) == lang {
+                    result.push(*item.clone());
+                }
+            }
+            _ => match localized.iter_mut().find(|x| x.0 == item.key() && x.1 > 0) {
+                Some(replaced) => replaced.1 -= 1,
+                None => result.push(item.clone()),
+            },
+        }
+    }
     result
 }
 
 pub fn parse_comment(comment_str: &str) -> Vec<CommentItem> {
-    let s = Span::from(comment_str);
+    parse_comment_with_tags(comment_str, &[], false)
+}
 
-    if let Ok((_, items)) = alt((oneline_comment, multiline_comment))(s) {
-        post_process(items)
-    } else {
-        vec![]
+/// Like `parse_comment`, also recognising the custom `tags` of the configuration.
+/// With `autobrief`, the first sentence of text without `@brief` is the brief, the rest details.
+pub fn parse_comment_with_tags(
+    comment_str: &str,
+    tags: &[CustomTag],
+    autobrief: bool,
+) -> Vec<CommentItem> {
+    let Some(text) = comment_text(comment_str) else {
+        return vec![];
+    };
+    let items = many0(comment_item(tags))(Span::from(text.as_str()));
+    match items {
+        Ok((_, items)) => post_process(items, autobrief),
+        Err(_) => vec![],
+    }
+}
+
+/// Commands that are neither built in nor among the custom `tags`, as `(line, name)` with the
+/// line counted from the first line of the comment. Their lines are kept as text of the
+/// preceding item.
+pub fn unknown_commands(comment_str: &str, tags: &[CustomTag]) -> Vec<(usize, String)> {
+    let Some(text) = comment_text(comment_str) else {
+        return vec![];
+    };
+    let mut result = Vec::new();
+    let mut s = Span::from(text.as_str());
+    let item = comment_item(tags);
+    while let Ok((rest, x)) = item(s) {
+        if let CommentItem::Plain(x) = &x {
+            let name: String = x
+                .strip_prefix('@')
+                .unwrap_or_default()
+                .chars()
+                .take_while(|x| x.is_ascii_alphanumeric() || *x == '_')
+                .collect();
+            if name.starts_with(|x: char| x.is_ascii_alphabetic()) {
+                result.push((s.location_line() as usize, name));
+            }
+        }
+        if rest.location_offset() == s.location_offset() {
+            break;
+        }
+        s = rest;
     }
+    result
 }
 
 #[test]
@@ -334,6 +1197,7 @@ fn test_parse_comment7() {
             CommentItem::Note(
                 "some notes
 more notes
+
 and more notes"
                     .to_string()
             ),
@@ -341,3 +1205,522 @@ and more notes"
         ]
     );
 }
+
+#[test]
+fn test_parse_comment_lang() {
+    let input = "/**
+    * @brief test
+    * @brief[zh] 测试
+    * @port[en] a port a
+    * @port[zh] a 端口a
+    * @note note
+    */";
+    let items = parse_comment(input);
+    assert_eq!(
+        items[1],
+        CommentItem::Lang {
+            lang: "zh".to_string(),
+            item: Box::new(CommentItem::Brief("测试".to_string()))
+        }
+    );
+    assert_eq!(
+        select_language(items.clone(), Some("zh")),
+        vec![
+            CommentItem::Brief("测试".to_string()),
+            CommentItem::Port {
+                name: "a".to_string(),
+                desc: "端口a".to_string()
+            },
+            CommentItem::Note("note".to_string())
+        ]
+    );
+    assert_eq!(
+        select_language(items, None),
+        vec![
+            CommentItem::Brief("test".to_string()),
+            CommentItem::Note("note".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_select_language_notes() {
+    let input = "/**
+    * @note first
+    * @note[zh] 第一
+    * @note second
+    */";
+    let notes = |lang| -> Vec<CommentItem> { select_language(parse_comment(input), lang) };
+    // the untranslated second note is kept
+    assert_eq!(
+        notes(Some("zh")),
+        vec![
+            CommentItem::Note("第一".to_string()),
+            CommentItem::Note("second".to_string())
+        ]
+    );
+    assert_eq!(
+        notes(None),
+        vec![
+            CommentItem::Note("first".to_string()),
+            CommentItem::Note("second".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_parse_comment_code() {
+    let input = "/**
+    * @brief test
+    * @code{.sv}
+    *   always_ff @(posedge clk) begin
+    *
+    *       q <= d;
+    *   end
+    * @endcode
+    * @example
+    *   assign a = b;
+    *
+    *     test2 u_test(a,b,c);
+    *
+    * @author a
+    */";
+    assert_eq!(
+        parse_comment(input),
+        vec![
+            CommentItem::Brief("test".to_string()),
+            CommentItem::Code {
+                lang: Some("sv".to_string()),
+                code: "always_ff @(posedge clk) begin\n\n    q <= d;\nend".to_string()
+            },
+            CommentItem::Example {
+                lang: Some("systemverilog".to_string()),
+                code: "assign a = b;\n\n  test2 u_test(a,b,c);".to_string(),
+                file: None,
+                line: 9
+            },
+            CommentItem::Author("a".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_parse_comment_example_events() {
+    let input = "/**
+    * @example
+    *   @(posedge clk);
+    *   @ (negedge rst_n) req <= 1'b1;
+    * @endexample
+    * after the example
+    * @example{.sv}
+    *   repeat (2) @(posedge clk);
+    * @note done
+    */";
+    let items = parse_comment(input);
+    let examples: Vec<&str> = items
+        .iter()
+        .filter_map(|x| match x {
+            CommentItem::Example { code, .. } => Some(code.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        examples,
+        vec![
+            "@(posedge clk);\n@ (negedge rst_n) req <= 1'b1;",
+            "repeat (2) @(posedge clk);"
+        ]
+    );
+    assert_eq!(items.last(), Some(&CommentItem::Note("done".to_string())));
+}
+
+#[test]
+fn test_parse_comment_markdown() {
+    let input = "/**
+    * @brief test
+    * @note a list:
+    * - first
+    *   - nested
+    * * second | third
+    *
+    * last paragraph
+    */";
+    assert_eq!(
+        parse_comment(input),
+        vec![
+            CommentItem::Brief("test".to_string()),
+            CommentItem::Note(
+                "a list:\n- first\n  - nested\n* second | third\n\nlast paragraph".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_comment_custom() {
+    use crate::config::{TagScope, TagStyle};
+
+    let tags = vec![
+        CustomTag {
+            name: "latency".to_string(),
+            label: None,
+            shape: TagShape::Simple,
+            scope: vec![],
+            style: TagStyle::Paragraph,
+        },
+        CustomTag {
+            name: "cdc".to_string(),
+            label: None,
+            shape: TagShape::Pair,
+            scope: vec![TagScope::Port],
+            style: TagStyle::Column,
+        },
+    ];
+    let input = "/**
+    * @brief test
+    * @latency 2 cycles
+    * @cdc din: 2-FF synchronizer
+    * @owner someone
+    */";
+    assert_eq!(
+        parse_comment_with_tags(input, &tags, false),
+        vec![
+            CommentItem::Brief("test".to_string()),
+            CommentItem::Custom {
+                tag: "latency".to_string(),
+                name: None,
+                desc: "2 cycles".to_string()
+            },
+            CommentItem::Custom {
+                tag: "cdc".to_string(),
+                name: Some("din".to_string()),
+                desc: "2-FF synchronizer\n@owner someone".to_string()
+            },
+        ]
+    );
+    // the undeclared `@owner` is reported
+    assert_eq!(
+        unknown_commands(input, &tags),
+        vec![(5, "owner".to_string())]
+    );
+    assert!(unknown_commands("/// @example\n/// @(posedge clk);", &[]).is_empty());
+}
+
+#[test]
+fn test_parse_comment_lifecycle() {
+    let input = "/**
+    * @brief test
+    * @deprecated use test2 instead
+    * @since 1.2
+    * @version 2.0.1
+    * @deprecated a: tie to 0
+    * @since[zh] b: 1.3
+    */";
+    assert_eq!(
+        parse_comment(input),
+        vec![
+            CommentItem::Brief("test".to_string()),
+            CommentItem::Deprecated {
+                name: None,
+                desc: "use test2 instead".to_string()
+            },
+            CommentItem::Since {
+                name: None,
+                version: "1.2".to_string()
+            },
+            CommentItem::Version {
+                name: None,
+                version: "2.0.1".to_string()
+            },
+            CommentItem::Deprecated {
+                name: Some("a".to_string()),
+                desc: "tie to 0".to_string()
+            },
+            CommentItem::Lang {
+                lang: "zh".to_string(),
+                item: Box::new(CommentItem::Since {
+                    name: Some("b".to_string()),
+                    version: "1.3".to_string()
+                })
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_comment_open() {
+    let input = "/**
+    * @brief test
+    * @todo support bursts
+    * longer than 16
+    * @bug: drops the last beat
+    */";
+    assert_eq!(
+        parse_comment(input),
+        vec![
+            CommentItem::Brief("test".to_string()),
+            CommentItem::Open {
+                kind: OpenKind::Todo,
+                text: "support bursts\nlonger than 16".to_string(),
+                file: None,
+                line: 3
+            },
+            CommentItem::Open {
+                kind: OpenKind::Bug,
+                text: "drops the last beat".to_string(),
+                file: None,
+                line: 5
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_comment_group() {
+    let input = "/**
+    * @defgroup bus Bus components
+    * Everything on the system bus.
+    * @ingroup soc
+    * @{
+    */";
+    assert_eq!(
+        parse_comment(input),
+        vec![
+            CommentItem::DefGroup {
+                name: "bus".to_string(),
+                title: "Bus components".to_string()
+            },
+            CommentItem::Brief("Everything on the system bus.".to_string()),
+            CommentItem::InGroup("soc".to_string()),
+            CommentItem::GroupOpen,
+        ]
+    );
+    assert_eq!(parse_comment("/** @} */"), vec![CommentItem::GroupClose]);
+}
+
+#[test]
+fn test_parse_comment_register() {
+    let input = "/**
+    * @brief test
+    * @reg CTRL offset=0x10 access=RW reset=0x0: control
+    * @field mode[7:4] access=RO: operating mode
+    * @field en[0]
+    */";
+    assert_eq!(
+        parse_comment(input),
+        vec![
+            CommentItem::Brief("test".to_string()),
+            CommentItem::Reg {
+                name: "CTRL".to_string(),
+                attrs: vec![
+                    ("offset".to_string(), "0x10".to_string()),
+                    ("access".to_string(), "RW".to_string()),
+                    ("reset".to_string(), "0x0".to_string()),
+                ],
+                desc: "control".to_string()
+            },
+            CommentItem::Field {
+                name: "mode".to_string(),
+                bits: "7:4".to_string(),
+                attrs: vec![("access".to_string(), "RO".to_string())],
+                desc: "operating mode".to_string()
+            },
+            CommentItem::Field {
+                name: "en".to_string(),
+                bits: "0".to_string(),
+                attrs: vec![],
+                desc: "".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_comment_diagram() {
+    let input = "/**
+    * @brief test
+    * @dot
+    * digraph G {
+    *     a -> b
+    * }
+    * @enddot
+    * @mermaid
+    * graph LR
+    * @endmermaid
+    */";
+    assert_eq!(
+        parse_comment(input),
+        vec![
+            CommentItem::Brief("test".to_string()),
+            CommentItem::Diagram {
+                kind: DiagramKind::Dot,
+                source: "digraph G {\n    a -> b\n}".to_string()
+            },
+            CommentItem::Diagram {
+                kind: DiagramKind::Mermaid,
+                source: "graph LR".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_comment_image() {
+    let input = "/**
+    * @brief test
+    * @image doc/timing.svg \"read timing\"
+    * @image block.png
+    */";
+    assert_eq!(
+        parse_comment(input),
+        vec![
+            CommentItem::Brief("test".to_string()),
+            CommentItem::Image {
+                path: "doc/timing.svg".to_string(),
+                caption: Some("read timing".to_string())
+            },
+            CommentItem::Image {
+                path: "block.png".to_string(),
+                caption: None
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_comment_styles() {
+    assert_eq!(
+        parse_comment("/// first line\n/// second line\n/// @note note"),
+        vec![
+            CommentItem::Brief("first line\nsecond line".to_string()),
+            CommentItem::Note("note".to_string()),
+        ]
+    );
+    assert_eq!(
+        parse_comment("//! test"),
+        vec![CommentItem::Brief("test".to_string())]
+    );
+    assert_eq!(
+        parse_comment("/*! test */"),
+        vec![CommentItem::Brief("test ".to_string())]
+    );
+    assert_eq!(
+        parse_comment("///< data in"),
+        vec![CommentItem::Brief("data in".to_string())]
+    );
+    assert!(is_trailing_comment("//*< data in"));
+    assert!(!is_trailing_comment("/// data in"));
+    assert_eq!(parse_comment("////////////////"), vec![]);
+}
+
+#[test]
+fn test_parse_comment_details() {
+    let input = "/**
+    * A FIFO. It buffers data
+    * between clock domains.
+    * @note async
+    */";
+    assert_eq!(
+        parse_comment_with_tags(input, &[], true),
+        vec![
+            CommentItem::Brief("A FIFO.".to_string()),
+            CommentItem::Details("It buffers data\nbetween clock domains.".to_string()),
+            CommentItem::Note("async".to_string()),
+        ]
+    );
+    let input = "/**
+    * @brief A FIFO. Really.
+    * @details It buffers data
+    * between clock domains.
+    */";
+    assert_eq!(
+        parse_comment_with_tags(input, &[], true),
+        vec![
+            CommentItem::Brief("A FIFO. Really.".to_string()),
+            CommentItem::Details("It buffers data\nbetween clock domains.".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_comment_name_patterns() {
+    let input = "/**
+    * @port a, b,c: inputs
+    * @port s_axi_*: AXI slave interface
+    * @param W? widths
+    */";
+    assert_eq!(
+        parse_comment(input),
+        vec![
+            CommentItem::Port {
+                name: "a,b,c".to_string(),
+                desc: " inputs".to_string()
+            },
+            CommentItem::Port {
+                name: "s_axi_*".to_string(),
+                desc: " AXI slave interface".to_string()
+            },
+            CommentItem::Param {
+                name: "W?".to_string(),
+                desc: "widths".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_comment_clocking() {
+    let input = "/**
+    * @clock clk: system clock
+    * @reset rst_n: active-low async
+    * @domain s_axi_*, irq: clk
+    * @domain clk_b
+    */";
+    assert_eq!(
+        parse_comment(input),
+        vec![
+            CommentItem::Clock {
+                name: Some("clk".to_string()),
+                desc: "system clock".to_string()
+            },
+            CommentItem::Reset {
+                name: Some("rst_n".to_string()),
+                desc: "active-low async".to_string()
+            },
+            CommentItem::Domain {
+                name: Some("s_axi_*,irq".to_string()),
+                clock: "clk".to_string()
+            },
+            CommentItem::Domain {
+                name: None,
+                clock: "clk_b".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_comment_req() {
+    let input = "/**
+    * @req R-1, R-2
+    * @req IDLE -> BUSY: R-3
+    * @req din: R-4
+    */";
+    assert_eq!(
+        parse_comment(input),
+        vec![
+            CommentItem::Req {
+                name: None,
+                ids: "R-1, R-2".to_string()
+            },
+            CommentItem::Req {
+                name: Some("IDLE->BUSY".to_string()),
+                ids: "R-3".to_string()
+            },
+            CommentItem::Req {
+                name: Some("din".to_string()),
+                ids: "R-4".to_string()
+            },
+        ]
+    );
+}
@@ -0,0 +1,122 @@
+use crate::comment_parser::COMMANDS;
+use serde::Deserialize;
+use std::{error::Error, fs};
+
+/// Project configuration, read from the file given by `--config`.
+///
+/// ```toml
+/// [[tag]]
+/// name = "latency"
+/// label = "Latency"
+/// scope = ["module", "function"]
+///
+/// [[tag]]
+/// name = "owner"
+/// style = "admonition"
+///
+/// [[tag]]
+/// name = "cdc"
+/// shape = "pair"
+/// scope = ["port"]
+/// style = "column"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Config {
+    #[serde(default, rename = "tag")]
+    pub tags: Vec<CustomTag>,
+}
+
+/// A project-specific command such as `@latency 2 cycles` or `@cdc din: 2-FF synchronizer`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CustomTag {
+    pub name: String,
+    pub label: Option<String>,
+    #[serde(default)]
+    pub shape: TagShape,
+    /// Entities the tag may document; empty means everywhere.
+    #[serde(default)]
+    pub scope: Vec<TagScope>,
+    #[serde(default)]
+    pub style: TagStyle,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagShape {
+    /// `@tag text`
+    #[default]
+    Simple,
+    /// `@tag name: text`
+    Pair,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagScope {
+    Module,
+    Port,
+    Function,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagStyle {
+    /// `**Label:** text`
+    #[default]
+    Paragraph,
+    /// `> **Label:** text`
+    Admonition,
+    /// An extra column of the port table
+    Column,
+}
+
+impl Config {
+    /// Reads the configuration, rejecting tags named like a built-in command.
+    pub fn from_file(file: &str) -> Result<Config, Box<dyn Error>> {
+        let content = fs::read_to_string(file)?;
+        let config: Config = toml::from_str(content.as_str())?;
+        if let Some(x) = config
+            .tags
+            .iter()
+            .find(|x| COMMANDS.contains(&x.name.as_str()))
+        {
+            return Err(format!("tag {} is a built-in command", x.name).into());
+        }
+        Ok(config)
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&CustomTag> {
+        self.tags.iter().find(|x| x.name == name)
+    }
+}
+
+impl CustomTag {
+    pub fn label(&self) -> String {
+        self.label.clone().unwrap_or_else(|| {
+            let mut chars = self.name.chars();
+            chars
+                .next()
+                .map(|x| x.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn allows(&self, scope: TagScope) -> bool {
+        self.scope.is_empty() || self.scope.contains(&scope)
+    }
+}
+
+#[test]
+fn test_config_built_in_tag() {
+    let file = std::env::temp_dir().join(format!("sv-docgen-config-{}.toml", std::process::id()));
+    fs::write(
+        &file,
+        "[[tag]]\nname = \"latency\"\n\n[[tag]]\nname = \"clock\"\n",
+    )
+    .unwrap();
+    let error = Config::from_file(&file.to_string_lossy()).err().unwrap();
+    assert_eq!(error.to_string(), "tag clock is a built-in command");
+    fs::write(&file, "[[tag]]\nname = \"latency\"\n").unwrap();
+    assert!(Config::from_file(&file.to_string_lossy()).is_ok());
+    let _ = fs::remove_file(&file);
+}
@@ -0,0 +1,332 @@
+use crate::{
+    comment_parser::CommentItem,
+    docgen::{SvFile, SvFunctionTask, SvModule, SvParam, SvPort},
+};
+
+/// Documentation a `@copydoc` or `@copybrief` target refers to.
+enum Source {
+    Module(SvModule),
+    Task(SvFunctionTask),
+    /// Description of a port or param.
+    Text(String),
+}
+
+/// All modules of the parsed files, resolving copies into each other.
+struct Resolver<'a> {
+    modules: &'a [SvModule],
+}
+
+/// Target of a port or param description such as `@copydoc core.din`.
+fn copy_target(desc: &str) -> Option<&str> {
+    let desc = desc.trim();
+    desc.strip_prefix("@copydoc ")
+        .or_else(|| desc.strip_prefix("@copybrief "))
+        .map(|x| x.trim())
+}
+
+/// Items of a copied comment; open items, groups and version information stay with their entity.
+fn copied(items: &[CommentItem]) -> impl Iterator<Item = CommentItem> + '_ {
+    items
+        .iter()
+        .filter(|x| {
+            !matches!(
+                x,
+                CommentItem::Brief(_)
+                    | CommentItem::Open { .. }
+                    | CommentItem::InGroup(_)
+                    | CommentItem::Deprecated { .. }
+                    | CommentItem::Since { .. }
+                    | CommentItem::Version { .. }
+            )
+        })
+        .cloned()
+}
+
+/// Copies the descriptions of the same-named ports and params missing in `ports` and `params`.
+fn inherit(ports: &mut [SvPort], params: &mut [SvParam], from: (&[SvPort], &[SvParam])) {
+    for port in ports.iter_mut().filter(|x| x.comment.is_empty()) {
+        if let Some(x) = from.0.iter().find(|x| x.name == port.name) {
+            port.comment = x.comment.clone();
+        }
+    }
+    for param in params.iter_mut().filter(|x| x.comment.is_empty()) {
+        if let Some(x) = from.1.iter().find(|x| x.name == param.name) {
+            param.comment = x.comment.clone();
+        }
+    }
+}
+
+impl Resolver<'_> {
+    /// The module `index` with its copies resolved; `stack` holds the modules being resolved.
+    fn module(&self, index: usize, stack: &mut Vec<usize>) -> SvModule {
+        let mut module = self.modules[index].clone();
+        stack.push(index);
+
+        let mut comment = Vec::new();
+        for item in &module.comment {
+            let (target, brief_only) = match item {
+                CommentItem::CopyDoc(x) => (x.trim(), false),
+                CommentItem::CopyBrief(x) => (x.trim(), true),
+                _ => {
+                    comment.push(item.clone());
+                    continue;
+                }
+            };
+            match self.target(target, None, stack) {
+                Ok(Source::Module(x)) => {
+                    module.brief = module.brief.or(x.brief);
+                    if !brief_only {
+                        comment.extend(copied(&x.comment));
+                        inherit(&mut module.ports, &mut module.params, (&x.ports, &x.params));
+                    }
+                }
+                Ok(Source::Task(x)) => {
+                    module.brief = module.brief.or(x.brief);
+                    if !brief_only {
+                        comment.extend(copied(&x.comment));
+                    }
+                }
+                Ok(Source::Text(x)) => module.brief = module.brief.or(Some(x)),
+                Err(e) => warn(&module.name, target, &e),
+            }
+        }
+        module.comment = comment;
+
+        let name = module.name.clone();
+        self.descriptions(&name, &mut module.ports, &mut module.params, stack);
+        for task in module.tasks.iter_mut() {
+            let mut comment = Vec::new();
+            for item in &task.comment {
+                let (target, brief_only) = match item {
+                    CommentItem::CopyDoc(x) => (x.trim(), false),
+                    CommentItem::CopyBrief(x) => (x.trim(), true),
+                    _ => {
+                        comment.push(item.clone());
+                        continue;
+                    }
+                };
+                match self.target(target, None, stack) {
+                    Ok(Source::Task(x)) => {
+                        task.brief = task.brief.take().or(x.brief);
+                        if !brief_only {
+                            comment.extend(copied(&x.comment));
+                            inherit(&mut task.ports, &mut task.params, (&x.ports, &x.params));
+                        }
+                    }
+                    Ok(Source::Module(x)) => {
+                        task.brief = task.brief.take().or(x.brief);
+                        if !brief_only {
+                            comment.extend(copied(&x.comment));
+                        }
+                    }
+                    Ok(Source::Text(x)) => task.brief = task.brief.take().or(Some(x)),
+                    Err(e) => warn(&format!("{}.{}", name, task.name), target, &e),
+                }
+            }
+            task.comment = comment;
+            let entity = format!("{}.{}", name, task.name);
+            self.descriptions(&entity, &mut task.ports, &mut task.params, stack);
+        }
+
+        stack.pop();
+        module
+    }
+
+    /// Resolves the port and param descriptions given as `@copydoc target`, where a target
+    /// without member names the port or param of the same name.
+    fn descriptions(
+        &self,
+        entity: &str,
+        ports: &mut [SvPort],
+        params: &mut [SvParam],
+        stack: &mut Vec<usize>,
+    ) {
+        let descs = ports
+            .iter_mut()
+            .map(|x| (&x.name, &mut x.comment))
+            .chain(params.iter_mut().map(|x| (&x.name, &mut x.comment)));
+        for (name, desc) in descs {
+            let Some(target) = copy_target(desc).map(|x| x.to_string()) else {
+                continue;
+            };
+            match self.target(&target, Some(name), stack) {
+                Ok(Source::Text(x)) => *desc = x,
+                Ok(Source::Module(x)) => *desc = x.brief.unwrap_or_default(),
+                Ok(Source::Task(x)) => *desc = x.brief.unwrap_or_default(),
+                Err(e) => warn(&format!("{}.{}", entity, name), &target, &e),
+            }
+        }
+    }
+
+    /// Documentation of `target`, which is a module or `module.member`. A module stands for
+    /// its member `member` if given.
+    fn target(
+        &self,
+        target: &str,
+        member: Option<&str>,
+        stack: &mut Vec<usize>,
+    ) -> Result<Source, String> {
+        let parts: Vec<&str> = target
+            .split("::")
+            .flat_map(|x| x.split('.'))
+            .map(|x| x.trim_end_matches("()"))
+            .collect();
+        let (name, member) = match parts.as_slice() {
+            [name] => (*name, member),
+            [name, member] => (*name, Some(*member)),
+            _ => return Err("not a module or module member".to_string()),
+        };
+        let index = self
+            .modules
+            .iter()
+            .position(|x| x.name == name)
+            .ok_or_else(|| format!("no module {}", name))?;
+        let cycle = || Err(format!("copies itself through {}", name));
+        let module = if stack.contains(&index) {
+            // members of a module being resolved are taken as written
+            match member {
+                Some(_) => self.modules[index].clone(),
+                None => return cycle(),
+            }
+        } else {
+            self.module(index, stack)
+        };
+        let Some(member) = member else {
+            return Ok(Source::Module(module));
+        };
+        let desc = module
+            .ports
+            .iter()
+            .find(|x| x.name == member)
+            .map(|x| &x.comment)
+            .or_else(|| {
+                module
+                    .params
+                    .iter()
+                    .find(|x| x.name == member)
+                    .map(|x| &x.comment)
+            });
+        match desc {
+            Some(desc) if copy_target(desc).is_some() => cycle(),
+            Some(desc) => Ok(Source::Text(desc.clone())),
+            None => match module.tasks.into_iter().find(|x| x.name == member) {
+                Some(task) => Ok(Source::Task(task)),
+                None => Err(format!("no member {} in {}", member, name)),
+            },
+        }
+    }
+}
+
+fn warn(entity: &str, target: &str, error: &str) {
+    eprintln!("warning: {}: @copydoc {}: {}", entity, target, error);
+}
+
+/// Replaces `@copydoc` and `@copybrief` by the documentation they refer to, once all files
+/// are parsed. Copies forming a cycle are reported and dropped.
+pub fn resolve_copydoc(files: &mut [SvFile]) {
+    let modules: Vec<SvModule> = files
+        .iter()
+        .flat_map(|x| x.modules.iter().cloned())
+        .collect();
+    let resolver = Resolver { modules: &modules };
+    let targets = files.iter_mut().flat_map(|x| x.modules.iter_mut());
+    for (index, module) in targets.enumerate() {
+        *module = resolver.module(index, &mut Vec::new());
+    }
+}
+
+#[cfg(test)]
+fn parse_files(input: &str) -> Vec<SvFile> {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    vec![
+        Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+            .unwrap()
+            .parse_tree(),
+    ]
+}
+
+#[test]
+fn test_resolve_copydoc() {
+    let input = "/**
+ * @brief Core
+ * @note core note
+ */
+module core (
+    input clk, ///< clock
+    input din  ///< data in
+);
+endmodule
+
+/**
+ * @copydoc core
+ * @port din: own data
+ */
+module wrapper (
+    input clk,
+    input din,
+    input flush ///< @copydoc core.clk
+);
+endmodule
+";
+    let mut files = parse_files(input);
+    resolve_copydoc(&mut files);
+    let wrapper = &files[0].modules[1];
+    assert_eq!(wrapper.brief.as_deref(), Some("Core"));
+    assert!(wrapper
+        .comment
+        .iter()
+        .any(|x| matches!(x, CommentItem::Note(x) if x == "core note")));
+    assert!(!wrapper
+        .comment
+        .iter()
+        .any(|x| matches!(x, CommentItem::CopyDoc(_))));
+    assert_eq!(wrapper.ports[0].comment, "clock");
+    // an existing description is not overwritten
+    assert_eq!(wrapper.ports[1].comment.trim(), "own data");
+    assert_eq!(wrapper.ports[2].comment, "clock");
+}
+
+#[test]
+fn test_resolve_copydoc_errors() {
+    let input = "/// @copydoc b
+module a (
+    input x ///< @copydoc nowhere
+);
+endmodule
+
+/// @copydoc a
+module b (
+    input y ///< @copydoc a.z
+);
+endmodule
+";
+    let mut files = parse_files(input);
+    let modules: Vec<SvModule> = files[0].modules.clone();
+    let resolver = Resolver { modules: &modules };
+    // `a` copies `b`, which copies `a` again
+    assert_eq!(
+        resolver.target("a", None, &mut vec![0, 1]).err().as_deref(),
+        Some("copies itself through a")
+    );
+    assert_eq!(
+        resolver
+            .target("nowhere", None, &mut vec![])
+            .err()
+            .as_deref(),
+        Some("no module nowhere")
+    );
+    assert_eq!(
+        resolver.target("a.z", None, &mut vec![]).err().as_deref(),
+        Some("no member z in a")
+    );
+
+    // the cycle and the unknown targets are dropped
+    resolve_copydoc(&mut files);
+    let a = &files[0].modules[0];
+    assert_eq!(a.brief, None);
+    assert!(a.comment.is_empty());
+    assert_eq!(a.ports[0].comment, "@copydoc nowhere");
+    assert_eq!(files[0].modules[1].ports[0].comment, "@copydoc a.z");
+}
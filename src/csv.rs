@@ -0,0 +1,324 @@
+use crate::{
+    comment_parser::OpenKind,
+    docgen::{ModuleKind, SvFile, SvTrace, TraceKind},
+    generator::DocgenGenerator,
+};
+
+/// One CSV record; fields with commas, quotes or line breaks are quoted.
+fn csv_record(fields: &[&str]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|x| {
+            if x.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", x.replace('"', "\"\""))
+            } else {
+                x.to_string()
+            }
+        })
+        .collect();
+    format!("{}\n", fields.join(","))
+}
+
+/// Records of a CSV text; quoted fields may contain commas, quotes and line breaks.
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut result = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => (),
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                result.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        result.push(record);
+    }
+    result
+}
+
+/// Requirements of a CSV file as `(id, title)`, from its first two columns. A header
+/// starting with `id` or `requirement` is skipped.
+pub fn parse_requirements(text: &str) -> Vec<(String, String)> {
+    let mut records = parse_csv(text).into_iter().peekable();
+    if let Some(header) = records.peek() {
+        let first = header.first().map(|x| x.trim().to_lowercase());
+        if matches!(first.as_deref(), Some("id") | Some("requirement")) {
+            records.next();
+        }
+    }
+    records
+        .filter_map(|x| {
+            let mut fields = x.into_iter().map(|x| x.trim().to_string());
+            let id = fields.next().filter(|x| !x.is_empty())?;
+            Some((id, fields.next().unwrap_or_default()))
+        })
+        .collect()
+}
+
+/// The `requirements` no design element covers, and the requirements of `traces` that are
+/// not among `requirements`.
+pub fn requirement_warnings(requirements: &[(String, String)], traces: &[SvTrace]) -> Vec<String> {
+    let uncovered = requirements
+        .iter()
+        .filter(|(id, _)| !traces.iter().any(|x| &x.requirement == id))
+        .map(|(id, _)| format!("requirement {} is not covered by any design element", id));
+    let unknown = traces
+        .iter()
+        .filter(|x| !requirements.iter().any(|y| y.0 == x.requirement))
+        .map(|x| format!("{}: unknown requirement {}", x.entity, x.requirement));
+    uncovered.chain(unknown).collect()
+}
+
+fn trace_kind(kind: TraceKind) -> &'static str {
+    match kind {
+        TraceKind::Module(ModuleKind::Module) => "module",
+        TraceKind::Module(ModuleKind::Interface) => "interface",
+        TraceKind::Module(ModuleKind::Package) => "package",
+        TraceKind::Function => "function",
+        TraceKind::Task => "task",
+        TraceKind::Port => "port",
+        TraceKind::Transition => "transition",
+    }
+}
+
+/// Exports the traceability matrix of the `@req` requirements, with a record without entity
+/// for each of `requirements` that no design element covers.
+pub struct TraceCsvGenerator {
+    pub requirements: Vec<(String, String)>,
+}
+
+impl DocgenGenerator for TraceCsvGenerator {
+    fn generate(&self, items: Vec<SvFile>) -> String {
+        let mut result = csv_record(&["requirement", "title", "file", "module", "kind", "entity"]);
+        let title = |id: &str| {
+            self.requirements
+                .iter()
+                .find(|x| x.0 == id)
+                .map(|x| x.1.clone())
+                .unwrap_or_default()
+        };
+        let mut covered = Vec::new();
+        for file in &items {
+            for trace in file.traces() {
+                result.push_str(
+                    csv_record(&[
+                        trace.requirement.as_str(),
+                        title(&trace.requirement).as_str(),
+                        file.name.as_str(),
+                        trace.module.as_str(),
+                        trace_kind(trace.kind),
+                        trace.entity.as_str(),
+                    ])
+                    .as_str(),
+                );
+                covered.push(trace.requirement);
+            }
+        }
+        for (id, title) in &self.requirements {
+            if !covered.contains(id) {
+                result.push_str(csv_record(&[id, title, "", "", "", ""]).as_str());
+            }
+        }
+        result
+    }
+}
+
+/// Exports the `@todo`, `@bug` and `@fixme` items of all files.
+pub struct OpenItemsCsvGenerator;
+
+impl DocgenGenerator for OpenItemsCsvGenerator {
+    fn generate(&self, items: Vec<SvFile>) -> String {
+        let mut result = csv_record(&["file", "line", "module", "entity", "kind", "text"]);
+        for file in &items {
+            for item in file.open_items() {
+                let kind = match item.kind {
+                    OpenKind::Todo => "todo",
+                    OpenKind::Bug => "bug",
+                    OpenKind::Fixme => "fixme",
+                };
+                result.push_str(
+                    csv_record(&[
+                        item.file.as_str(),
+                        item.line.to_string().as_str(),
+                        item.module.as_str(),
+                        item.entity.as_str(),
+                        kind,
+                        item.text.as_str(),
+                    ])
+                    .as_str(),
+                );
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+fn parse_file(input: &str, name: &str) -> SvFile {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    Docgen::new(input, name, &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree()
+}
+
+#[test]
+fn test_parse_csv() {
+    let fields = ["plain", "a, b", "say \"hi\"", "two\nlines", ""];
+    let text = csv_record(&fields) + "x,y\r\n";
+    assert_eq!(
+        text.lines().next(),
+        Some("plain,\"a, b\",\"say \"\"hi\"\"\",\"two")
+    );
+    assert_eq!(
+        parse_csv(&text),
+        vec![
+            fields.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+            vec!["x".to_string(), "y".to_string()],
+        ]
+    );
+    // a last record without line break
+    assert_eq!(parse_csv("a,b"), vec![vec!["a", "b"]]);
+}
+
+#[test]
+fn test_open_items_csv() {
+    let input = "/// @todo add a FIFO, or two
+module m #(
+    parameter W = 8 ///< @fixme check the \"max\" width
+) (
+    input a, ///< data
+             ///< @todo register a
+    input b
+);
+    /// @bug off by one
+    function f (input x);
+    endfunction
+endmodule
+";
+    let file = parse_file(input, "m.sv");
+    let csv = OpenItemsCsvGenerator.generate(vec![file]);
+    let records: Vec<Vec<String>> = parse_csv(&csv);
+    let fields = |x: &Vec<String>| (x[1].clone(), x[3].clone(), x[4].clone(), x[5].clone());
+    assert_eq!(
+        records[0],
+        vec!["file", "line", "module", "entity", "kind", "text"]
+    );
+    assert!(records[1..].iter().all(|x| x[0] == "m.sv" && x[2] == "m"));
+    assert_eq!(
+        records[1..].iter().map(fields).collect::<Vec<_>>(),
+        vec![
+            (
+                "1".to_string(),
+                "m".to_string(),
+                "todo".to_string(),
+                "add a FIFO, or two".to_string()
+            ),
+            (
+                "6".to_string(),
+                "m.a".to_string(),
+                "todo".to_string(),
+                "register a".to_string()
+            ),
+            (
+                "3".to_string(),
+                "m.W".to_string(),
+                "fixme".to_string(),
+                "check the \"max\" width".to_string()
+            ),
+            (
+                "9".to_string(),
+                "m.f".to_string(),
+                "bug".to_string(),
+                "off by one".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_requirements() {
+    let text = "ID,Title,Owner\r\nSYS-1,\"Reset, then idle\",hw\r\n\r\nSYS-2,\"The \"\"busy\"\" flag\"\r\n ,no id\r\nSYS-3\r\n";
+    assert_eq!(
+        parse_requirements(text),
+        vec![
+            ("SYS-1".to_string(), "Reset, then idle".to_string()),
+            ("SYS-2".to_string(), "The \"busy\" flag".to_string()),
+            ("SYS-3".to_string(), String::new()),
+        ]
+    );
+    // without header
+    assert_eq!(
+        parse_requirements("SYS-1,first\n"),
+        vec![("SYS-1".to_string(), "first".to_string())]
+    );
+}
+
+#[cfg(test)]
+const TRACE_INPUT: &str = "/// @req SYS-1, SYS-2
+/// @req IDLE -> BUSY: SYS-3
+module m (
+    input a, ///< @req SYS-2
+    input b
+);
+    /// @req SYS-9
+    task t;
+    endtask
+endmodule
+";
+
+#[test]
+fn test_trace_csv() {
+    let file = parse_file(TRACE_INPUT, "m.sv");
+    let generator = TraceCsvGenerator {
+        requirements: vec![
+            ("SYS-1".to_string(), "Reset, then idle".to_string()),
+            ("SYS-2".to_string(), "Inputs".to_string()),
+            ("SYS-3".to_string(), "Start".to_string()),
+            ("SYS-4".to_string(), "Stop".to_string()),
+        ],
+    };
+    assert_eq!(
+        generator.generate(vec![file]),
+        "requirement,title,file,module,kind,entity
+SYS-1,\"Reset, then idle\",m.sv,m,module,m
+SYS-2,Inputs,m.sv,m,module,m
+SYS-3,Start,m.sv,m,transition,m: IDLE -> BUSY
+SYS-2,Inputs,m.sv,m,port,m.a
+SYS-9,,m.sv,m,task,m.t
+SYS-4,Stop,,,,
+"
+    );
+}
+
+#[test]
+fn test_requirement_warnings() {
+    let file = parse_file(TRACE_INPUT, "m.sv");
+    let requirements = vec![
+        ("SYS-1".to_string(), String::new()),
+        ("SYS-2".to_string(), String::new()),
+        ("SYS-3".to_string(), String::new()),
+        ("SYS-4".to_string(), String::new()),
+    ];
+    assert_eq!(
+        requirement_warnings(&requirements, &file.traces()),
+        vec![
+            "requirement SYS-4 is not covered by any design element",
+            "m.t: unknown requirement SYS-9",
+        ]
+    );
+}
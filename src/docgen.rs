@@ -1,24 +1,50 @@
-use crate::comment_parser::parse_comment;
+use crate::bus::port_groups;
+use crate::comment_parser::is_line_comment;
+use crate::comment_parser::is_trailing_comment;
+use crate::comment_parser::parse_comment_with_tags;
+use crate::comment_parser::select_language;
+use crate::comment_parser::unknown_commands;
 use crate::comment_parser::CommentItem;
+use crate::comment_parser::OpenKind;
+use crate::config::CustomTag;
+use crate::config::TagScope;
+use crate::symbols::is_url;
+use chardetng::EncodingDetector;
+use encoding_rs::Encoding;
+use encoding_rs::UTF_8;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::error::Error;
+use std::fmt;
 use std::fs;
+use std::path::Path;
 use std::path::PathBuf;
 use sv_parser::parse_sv_str;
+use sv_parser::AlwaysConstruct;
+use sv_parser::AlwaysKeyword;
 use sv_parser::AnsiPortDeclaration;
 use sv_parser::DataTypeOrImplicit;
 use sv_parser::Define;
+use sv_parser::ListOfParameterAssignments;
+use sv_parser::ListOfPortConnections;
 use sv_parser::Locate;
+use sv_parser::NamedPortConnection;
 use sv_parser::NetPortHeaderOrInterfacePortHeader;
 use sv_parser::NodeEvent;
 use sv_parser::ParamAssignment;
 use sv_parser::RefNode;
 use sv_parser::RefNodes;
 use sv_parser::SyntaxTree;
+use sv_parser::TypeDeclaration;
 
 pub struct Docgen {
     file: String,
+    source: String,
     tree: SyntaxTree,
+    lang: Option<String>,
+    includes: Vec<(String, String)>,
+    tags: Vec<CustomTag>,
+    autobrief: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -28,6 +54,16 @@ pub struct SvPort {
     pub direction: Option<String>,
     pub dimensions: Option<String>,
     pub comment: String,
+    /// Custom tags documenting the port, as `(tag, text)`.
+    pub tags: Vec<(String, String)>,
+    pub lifecycle: Lifecycle,
+    /// Child port `module.port` the comment is inherited from through a direct connection.
+    pub inherited: Option<String>,
+    pub clocking: Clocking,
+    /// Requirements given by `@req`.
+    pub reqs: Vec<String>,
+    /// `@todo`, `@bug` and `@fixme` items of the port.
+    pub open: Vec<CommentItem>,
 }
 
 #[derive(Debug, Clone)]
@@ -37,6 +73,35 @@ pub struct SvParam {
     pub default: Option<String>,
     pub dimensions: Option<String>,
     pub comment: String,
+    pub lifecycle: Lifecycle,
+    /// `@todo`, `@bug` and `@fixme` items of the param.
+    pub open: Vec<CommentItem>,
+}
+
+/// Version information given by `@deprecated`, `@since` and `@version`.
+#[derive(Debug, Clone, Default)]
+pub struct Lifecycle {
+    /// Reason of the deprecation, possibly empty.
+    pub deprecated: Option<String>,
+    pub since: Option<String>,
+    pub version: Option<String>,
+}
+
+/// Clocking of a port given by `@clock`, `@reset` and `@domain`, or inferred from the
+/// sensitivity lists of `always_ff` blocks.
+#[derive(Debug, Clone, Default)]
+pub struct Clocking {
+    pub clock: bool,
+    pub reset: Option<SvReset>,
+    /// Clock the port is synchronous to.
+    pub domain: Option<String>,
+}
+
+/// Polarity and kind of a reset, as far as known.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SvReset {
+    pub active_low: Option<bool>,
+    pub asynchronous: Option<bool>,
 }
 
 #[derive(Debug, Clone)]
@@ -46,9 +111,17 @@ pub struct SvState {
     pub comment: Vec<CommentItem>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModuleKind {
+    Module,
+    Interface,
+    Package,
+}
+
 #[derive(Debug, Clone)]
 pub struct SvModule {
     pub name: String,
+    pub kind: ModuleKind,
     pub brief: Option<String>,
     pub ports: Vec<SvPort>,
     pub params: Vec<SvParam>,
@@ -56,6 +129,132 @@ pub struct SvModule {
     pub state_machines: Vec<SvStateMachine>,
     pub tasks: Vec<SvFunctionTask>,
     pub comment: Vec<CommentItem>,
+    pub lifecycle: Lifecycle,
+    /// Names of the groups the module belongs to.
+    pub groups: Vec<String>,
+    pub typedefs: Vec<SvTypedef>,
+    pub instances: Vec<SvInstance>,
+    pub registers: Vec<SvRegister>,
+    pub port_groups: Vec<SvPortGroup>,
+}
+
+/// A `typedef` of a module, interface or package.
+#[derive(Debug, Clone)]
+pub struct SvTypedef {
+    pub name: String,
+    /// The defined type, such as `struct packed { ... }`.
+    pub definition: String,
+    pub comment: String,
+}
+
+impl SvTypedef {
+    /// Members of a packed struct as `(name, width)` from MSB to LSB, `None` for other types
+    /// or when a width is not a number.
+    pub fn packed_fields(&self) -> Option<Vec<(String, u32)>> {
+        let body = self.definition.strip_prefix("struct packed")?;
+        let body = body[body.find('{')? + 1..body.rfind('}')?].trim();
+        let mut result = Vec::new();
+        for member in body.split(';').map(|x| x.trim()).filter(|x| !x.is_empty()) {
+            // `logic [3:0] a, b` -> type `logic [3:0]`, names `a`, `b`
+            let (declared, names) = match member.split_once(',') {
+                Some((first, rest)) => {
+                    let (declared, name) = first.rsplit_once(' ')?;
+                    (
+                        declared,
+                        std::iter::once(name).chain(rest.split(',')).collect(),
+                    )
+                }
+                None => {
+                    let (declared, name) = member.rsplit_once(' ')?;
+                    (declared, vec![name])
+                }
+            };
+            let base = declared.split(['[', ' ']).next()?;
+            let mut width: u32 = match base {
+                "logic" | "bit" | "reg" => 1,
+                "byte" => 8,
+                "shortint" => 16,
+                "int" | "integer" => 32,
+                "longint" => 64,
+                _ => return None,
+            };
+            for dimension in declared.split('[').skip(1) {
+                let (msb, lsb) = dimension.split(']').next()?.split_once(':')?;
+                width *= (parse_number(msb)? as i64 - parse_number(lsb)? as i64).unsigned_abs()
+                    as u32
+                    + 1;
+            }
+            for name in names {
+                result.push((name.trim().to_string(), width));
+            }
+        }
+        Some(result)
+    }
+}
+
+/// An instance of a module or interface.
+#[derive(Debug, Clone)]
+pub struct SvInstance {
+    pub module: String,
+    pub name: String,
+    /// Port connections as `(port, expression)`, the port is `None` for ordered connections.
+    pub connections: Vec<(Option<String>, String)>,
+    /// Whether the remaining ports are connected by name with `.*`.
+    pub wildcard: bool,
+    /// Parameter values as `(param, expression)`, the param is `None` for ordered values.
+    pub parameters: Vec<(Option<String>, String)>,
+}
+
+/// Ports documented together, given by `@portgroup` or detected as a standard bus interface.
+#[derive(Debug, Clone)]
+pub struct SvPortGroup {
+    pub name: String,
+    /// Detected bus protocol, e.g. `AXI4-Lite`.
+    pub protocol: Option<String>,
+    pub role: Option<BusRole>,
+    pub desc: String,
+    /// Names of the ports in the group.
+    pub ports: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BusRole {
+    Master,
+    Slave,
+}
+
+/// A register given by `@reg` with the `@field`s following it.
+#[derive(Debug, Clone)]
+pub struct SvRegister {
+    pub name: String,
+    pub offset: Option<String>,
+    pub access: Option<String>,
+    pub reset: Option<String>,
+    /// Width in bits, 32 unless given by `width=`.
+    pub width: u32,
+    pub desc: String,
+    pub fields: Vec<SvField>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SvField {
+    pub name: String,
+    /// `msb:lsb` or a single bit, as written.
+    pub bits: String,
+    /// Access of the field, or else of its register.
+    pub access: Option<String>,
+    pub reset: Option<String>,
+    pub desc: String,
+}
+
+/// A group declared with `@defgroup` or `@addtogroup`.
+#[derive(Debug, Clone)]
+pub struct SvGroup {
+    pub name: String,
+    pub title: String,
+    pub brief: Option<String>,
+    pub comment: Vec<CommentItem>,
+    pub parent: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -66,6 +265,11 @@ pub struct SvFile {
     pub rev: Vec<String>,
     pub modules: Vec<SvModule>,
     pub comment: Vec<CommentItem>,
+    /// `(including file, included file)` pairs of all files included from this file.
+    pub includes: Vec<(String, String)>,
+    pub groups: Vec<SvGroup>,
+    /// Local images given by `@image` that do not exist.
+    pub missing_images: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -77,6 +281,40 @@ pub struct SvFunctionTask {
     pub params: Vec<SvParam>,
     pub signals: Vec<SvSignal>,
     pub comment: Vec<CommentItem>,
+    pub lifecycle: Lifecycle,
+    /// Names of the groups given by `@ingroup`.
+    pub groups: Vec<String>,
+}
+
+/// A `@todo`, `@bug` or `@fixme` with the entity it was left on.
+#[derive(Debug, Clone)]
+pub struct SvOpenItem {
+    pub kind: OpenKind,
+    pub text: String,
+    pub file: String,
+    pub line: usize,
+    pub module: String,
+    /// `module` or `module.task`.
+    pub entity: String,
+}
+
+/// A design element traced to a requirement with `@req`.
+#[derive(Debug, Clone)]
+pub struct SvTrace {
+    pub requirement: String,
+    pub kind: TraceKind,
+    pub module: String,
+    /// `module`, `module.member` or `module: from -> to` for a state transition.
+    pub entity: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceKind {
+    Module(ModuleKind),
+    Function,
+    Task,
+    Port,
+    Transition,
 }
 
 #[derive(Debug, Clone)]
@@ -101,6 +339,23 @@ pub enum DocStackable {
     Task(SvFunctionTask),
 }
 
+impl SvPort {
+    /// Packed dimensions of the port type, e.g. `[9:0]` for `wire[9:0]`.
+    pub fn packed_dimensions(&self) -> Option<String> {
+        self.port_type
+            .as_ref()
+            .and_then(|x| x.find('[').map(|i| x[i..].trim().to_string()))
+    }
+
+    /// Port type without its packed dimensions, e.g. `wire` for `wire[9:0]`.
+    pub fn base_type(&self) -> Option<String> {
+        self.port_type
+            .as_ref()
+            .map(|x| x.split('[').next().unwrap_or("").trim().to_string())
+            .filter(|x| !x.is_empty())
+    }
+}
+
 impl SvFile {
     fn new(file: &str) -> SvFile {
         SvFile {
@@ -110,14 +365,139 @@ impl SvFile {
             rev: Vec::new(),
             modules: Vec::new(),
             comment: Vec::new(),
+            includes: Vec::new(),
+            groups: Vec::new(),
+            missing_images: Vec::new(),
         }
     }
 }
 
+/// Open items of the `ports` and `params` of `entity`, as `(entity.member, items)`.
+fn member_open_items<'a>(
+    entity: &str,
+    ports: &'a [SvPort],
+    params: &'a [SvParam],
+) -> Vec<(String, &'a Vec<CommentItem>)> {
+    let ports = ports.iter().map(|x| (&x.name, &x.open));
+    let params = params.iter().map(|x| (&x.name, &x.open));
+    ports
+        .chain(params)
+        .map(|(name, open)| (format!("{}.{}", entity, name), open))
+        .collect()
+}
+
+impl SvFile {
+    /// All open items of the modules, their functions/tasks and the ports and params of both,
+    /// in source order of each entity.
+    pub fn open_items(&self) -> Vec<SvOpenItem> {
+        let mut result = Vec::new();
+        for module in &self.modules {
+            let mut entities = vec![(module.name.clone(), &module.comment)];
+            entities.extend(member_open_items(
+                &module.name,
+                &module.ports,
+                &module.params,
+            ));
+            for task in &module.tasks {
+                let entity = format!("{}.{}", module.name, task.name);
+                entities.push((entity.clone(), &task.comment));
+                entities.extend(member_open_items(&entity, &task.ports, &task.params));
+            }
+            for (entity, comment) in entities {
+                for item in comment {
+                    if let CommentItem::Open {
+                        kind,
+                        text,
+                        file,
+                        line,
+                    } = item
+                    {
+                        result.push(SvOpenItem {
+                            kind: *kind,
+                            text: text.clone(),
+                            file: file.clone().unwrap_or_else(|| self.name.clone()),
+                            line: *line,
+                            module: module.name.clone(),
+                            entity: entity.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// The requirements given by `@req` on the modules, functions/tasks, ports and state
+    /// transitions of the file, one per requirement and entity.
+    pub fn traces(&self) -> Vec<SvTrace> {
+        let mut result = Vec::new();
+        for module in &self.modules {
+            let mut add = |kind: TraceKind, entity: String, ids: &str| {
+                for id in requirement_ids(ids) {
+                    result.push(SvTrace {
+                        requirement: id.to_string(),
+                        kind,
+                        module: module.name.clone(),
+                        entity: entity.clone(),
+                    });
+                }
+            };
+            for item in &module.comment {
+                match item {
+                    CommentItem::Req { name: None, ids } => {
+                        add(TraceKind::Module(module.kind), module.name.clone(), ids)
+                    }
+                    CommentItem::Req {
+                        name: Some(name),
+                        ids,
+                    } => {
+                        if let Some((from, to)) = name.split_once("->") {
+                            let entity = format!("{}: {} -> {}", module.name, from, to);
+                            add(TraceKind::Transition, entity, ids);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            for port in &module.ports {
+                let entity = format!("{}.{}", module.name, port.name);
+                add(TraceKind::Port, entity, &port.reqs.join(","));
+            }
+            for task in &module.tasks {
+                let kind = if task.is_function {
+                    TraceKind::Function
+                } else {
+                    TraceKind::Task
+                };
+                let entity = format!("{}.{}", module.name, task.name);
+                for item in &task.comment {
+                    if let CommentItem::Req { name: None, ids } = item {
+                        add(kind, entity.clone(), ids);
+                    }
+                }
+                for port in &task.ports {
+                    add(
+                        TraceKind::Port,
+                        format!("{}.{}", entity, port.name),
+                        &port.reqs.join(","),
+                    );
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Requirement IDs of a `@req`, separated by commas or spaces.
+pub fn requirement_ids(ids: &str) -> impl Iterator<Item = &str> {
+    ids.split([',', ' ', '\t']).filter(|x| !x.is_empty())
+}
+
 impl SvModule {
     fn new(name: &str) -> SvModule {
         SvModule {
             name: name.to_string(),
+            kind: ModuleKind::Module,
             brief: None,
             ports: Vec::new(),
             params: Vec::new(),
@@ -125,6 +505,12 @@ impl SvModule {
             state_machines: Vec::new(),
             tasks: Vec::new(),
             comment: Vec::new(),
+            lifecycle: Lifecycle::default(),
+            groups: Vec::new(),
+            typedefs: Vec::new(),
+            instances: Vec::new(),
+            registers: Vec::new(),
+            port_groups: Vec::new(),
         }
     }
 }
@@ -139,8 +525,407 @@ impl SvFunctionTask {
             signals: Vec::new(),
             comment: Vec::new(),
             is_function: is_func,
+            lifecycle: Lifecycle::default(),
+            groups: Vec::new(),
+        }
+    }
+}
+
+impl Lifecycle {
+    /// Version information in `items` about the documented entity itself, where a name such
+    /// as in `@deprecated Note: reason` is part of the text.
+    fn from_items(items: &[CommentItem]) -> Self {
+        let mut result = Lifecycle::default();
+        for item in items {
+            result.apply(&unnamed(item));
+        }
+        result
+    }
+
+    fn apply(&mut self, item: &CommentItem) {
+        match item {
+            CommentItem::Deprecated { desc, .. } => self.deprecated = Some(desc.clone()),
+            CommentItem::Since { version, .. } => self.since = Some(version.clone()),
+            CommentItem::Version { version, .. } => self.version = Some(version.clone()),
+            _ => (),
+        }
+    }
+
+    /// Takes the information missing in `self` from `other`.
+    fn merge(&mut self, other: Lifecycle) {
+        self.deprecated = self.deprecated.take().or(other.deprecated);
+        self.since = self.since.take().or(other.since);
+        self.version = self.version.take().or(other.version);
+    }
+}
+
+impl SvReset {
+    /// Reset given by the leading keywords of `desc`, e.g. `active-low async`, and the rest
+    /// of `desc`.
+    fn parse(desc: &str) -> (SvReset, String) {
+        let mut result = SvReset::default();
+        let mut rest = desc.trim();
+        while !rest.is_empty() {
+            let (word, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let word = word.trim_end_matches(',').to_lowercase().replace('_', "-");
+            match word.as_str() {
+                "active-low" => result.active_low = Some(true),
+                "active-high" => result.active_low = Some(false),
+                "async" | "asynchronous" => result.asynchronous = Some(true),
+                "sync" | "synchronous" => result.asynchronous = Some(false),
+                _ => break,
+            }
+            rest = tail.trim_start();
+        }
+        (result, rest.to_string())
+    }
+}
+
+impl Clocking {
+    /// Clocking in `items` about the documented port itself, and the description it gives.
+    fn from_items(items: &[CommentItem]) -> (Self, String) {
+        let mut result = Clocking::default();
+        let mut desc = String::new();
+        for item in items {
+            match item {
+                CommentItem::Clock { name: None, .. }
+                | CommentItem::Reset { name: None, .. }
+                | CommentItem::Domain { name: None, .. } => desc += &result.apply(item),
+                _ => (),
+            }
+        }
+        (result, desc)
+    }
+
+    /// Applies a `@clock`, `@reset` or `@domain`, returning the description of the port it gives.
+    fn apply(&mut self, item: &CommentItem) -> String {
+        match item {
+            CommentItem::Clock { desc, .. } => {
+                self.clock = true;
+                desc.trim().to_string()
+            }
+            CommentItem::Reset { desc, .. } => {
+                let (reset, desc) = SvReset::parse(desc);
+                self.reset = Some(reset);
+                desc
+            }
+            CommentItem::Domain { clock, .. } => {
+                self.domain = clock.split_whitespace().next().map(|x| x.to_string());
+                String::new()
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Takes the information missing in `self` from `other`.
+    fn merge(&mut self, other: Clocking) {
+        self.clock |= other.clock;
+        self.reset = match (self.reset, other.reset) {
+            (Some(x), Some(y)) => Some(SvReset {
+                active_low: x.active_low.or(y.active_low),
+                asynchronous: x.asynchronous.or(y.asynchronous),
+            }),
+            (x, y) => x.or(y),
+        };
+        self.domain = self.domain.take().or(other.domain);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.clock && self.reset.is_none() && self.domain.is_none()
+    }
+}
+
+/// Moves `@req` naming ports to them. Returns `comment` with the moved items removed.
+fn refine_reqs(comment: &[CommentItem], ports: &mut [SvPort]) -> Vec<CommentItem> {
+    let mut result = Vec::new();
+    for c in comment {
+        match c {
+            CommentItem::Req {
+                name: Some(name),
+                ids,
+            } if !name.contains("->") => {
+                for port in ports.iter_mut().filter(|x| matches_names(name, &x.name)) {
+                    port.reqs
+                        .extend(requirement_ids(ids).map(|x| x.to_string()));
+                }
+            }
+            _ => result.push(c.clone()),
+        }
+    }
+    result
+}
+
+/// Moves `@clock`, `@reset` and `@domain` naming ports to them, filling in their descriptions.
+/// Returns `comment` with the moved items removed.
+fn refine_clocking(comment: &[CommentItem], ports: &mut [SvPort]) -> Vec<CommentItem> {
+    let mut result = Vec::new();
+    for c in comment {
+        let name = match c {
+            CommentItem::Clock {
+                name: Some(name), ..
+            }
+            | CommentItem::Reset {
+                name: Some(name), ..
+            }
+            | CommentItem::Domain {
+                name: Some(name), ..
+            } => name,
+            _ => {
+                result.push(c.clone());
+                continue;
+            }
+        };
+        for port in ports.iter_mut().filter(|x| matches_names(name, &x.name)) {
+            let desc = port.clocking.apply(c);
+            if port.comment.is_empty() {
+                port.comment = desc;
+            }
+        }
+    }
+    result
+}
+
+/// A `@deprecated`, `@since` or `@version` item with its name as part of the text, e.g.
+/// `Note: reason` for `@deprecated Note: reason` not naming a port or param.
+fn unnamed(item: &CommentItem) -> CommentItem {
+    match item {
+        CommentItem::Deprecated {
+            name: Some(name),
+            desc,
+        } => CommentItem::Deprecated {
+            name: None,
+            desc: format!("{}: {}", name, desc),
+        },
+        CommentItem::Since {
+            name: Some(name),
+            version,
+        } => CommentItem::Since {
+            name: None,
+            version: format!("{}: {}", name, version),
+        },
+        CommentItem::Version {
+            name: Some(name),
+            version,
+        } => CommentItem::Version {
+            name: None,
+            version: format!("{}: {}", name, version),
+        },
+        _ => item.clone(),
+    }
+}
+
+/// Moves `@deprecated`, `@since` and `@version` naming ports or params, also by glob patterns,
+/// to them; the others document the entity itself. Returns `comment` with the moved items
+/// removed.
+fn refine_lifecycle(
+    comment: &[CommentItem],
+    lifecycle: &mut Lifecycle,
+    ports: &mut [SvPort],
+    params: &mut [SvParam],
+) -> Vec<CommentItem> {
+    let mut result = Vec::new();
+    for c in comment {
+        let name = match c {
+            CommentItem::Deprecated { name, .. }
+            | CommentItem::Since { name, .. }
+            | CommentItem::Version { name, .. } => name,
+            _ => {
+                result.push(c.clone());
+                continue;
+            }
+        };
+        if let Some(name) = name {
+            let mut matched = false;
+            for port in ports.iter_mut().filter(|x| matches_names(name, &x.name)) {
+                port.lifecycle.apply(c);
+                matched = true;
+            }
+            for param in params.iter_mut().filter(|x| matches_names(name, &x.name)) {
+                param.lifecycle.apply(c);
+                matched = true;
+            }
+            if matched {
+                continue;
+            }
+        }
+        // no such port or param, `name:` is part of the text
+        let c = unnamed(c);
+        lifecycle.apply(&c);
+        result.push(c);
+    }
+    result
+}
+
+/// Value of a number such as `0x10`, `0b101`, `16`, `'h10` or `8'h10`.
+pub fn parse_number(s: &str) -> Option<u64> {
+    let s = s.trim().replace('_', "");
+    let s = s.as_str();
+    let (radix, digits) = if let Some(x) = s.strip_prefix("0x").or(s.strip_prefix("0X")) {
+        (16, x)
+    } else if let Some(x) = s.strip_prefix("0b").or(s.strip_prefix("0B")) {
+        (2, x)
+    } else if let Some((_, x)) = s.split_once('\'') {
+        let x = x.trim_start_matches(['s', 'S']);
+        match x.chars().next().map(|c| c.to_ascii_lowercase()) {
+            Some('h') => (16, &x[1..]),
+            Some('b') => (2, &x[1..]),
+            Some('o') => (8, &x[1..]),
+            Some('d') => (10, &x[1..]),
+            _ => return None,
+        }
+    } else {
+        (10, s)
+    };
+    u64::from_str_radix(digits, radix).ok()
+}
+
+impl SvRegister {
+    /// Registers given by `@reg` and `@field` in `items`, and `items` without them.
+    fn from_items(items: &[CommentItem]) -> (Vec<SvRegister>, Vec<CommentItem>) {
+        let mut registers: Vec<SvRegister> = Vec::new();
+        let mut comment = Vec::new();
+        let attr = |attrs: &[(String, String)], key: &str| {
+            attrs.iter().find(|x| x.0 == key).map(|x| x.1.clone())
+        };
+        for item in items {
+            match item {
+                CommentItem::Reg { name, attrs, desc } => registers.push(SvRegister {
+                    name: name.clone(),
+                    offset: attr(attrs, "offset"),
+                    access: attr(attrs, "access"),
+                    reset: attr(attrs, "reset"),
+                    width: attr(attrs, "width")
+                        .and_then(|x| parse_number(&x))
+                        .map(|x| x as u32)
+                        .unwrap_or(32),
+                    desc: desc.clone(),
+                    fields: Vec::new(),
+                }),
+                CommentItem::Field {
+                    name,
+                    bits,
+                    attrs,
+                    desc,
+                } => match registers.last_mut() {
+                    Some(register) => register.fields.push(SvField {
+                        name: name.clone(),
+                        bits: bits.clone(),
+                        access: attr(attrs, "access").or(register.access.clone()),
+                        reset: attr(attrs, "reset"),
+                        desc: desc.clone(),
+                    }),
+                    // no register to belong to
+                    None => comment.push(item.clone()),
+                },
+                _ => comment.push(item.clone()),
+            }
+        }
+        (registers, comment)
+    }
+
+    /// Fields exceeding the register width and fields overlapping each other.
+    pub fn problems(&self) -> Vec<String> {
+        let mut result = Vec::new();
+        let ranges: Vec<(&SvField, Option<(u32, u32)>)> =
+            self.fields.iter().map(|x| (x, x.range())).collect();
+        for (i, (field, range)) in ranges.iter().enumerate() {
+            let Some((msb, lsb)) = range else {
+                continue;
+            };
+            if *msb >= self.width {
+                result.push(format!(
+                    "field {}[{}] of register {} exceeds its {} bits and is left out",
+                    field.name, field.bits, self.name, self.width
+                ));
+            }
+            for (other, other_range) in &ranges[..i] {
+                if let Some((other_msb, other_lsb)) = other_range {
+                    if lsb <= other_msb && other_lsb <= msb {
+                        result.push(format!(
+                            "field {}[{}] of register {} overlaps {}[{}]",
+                            field.name, field.bits, self.name, other.name, other.bits
+                        ));
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+impl SvField {
+    /// `(msb, lsb)` of the field, `None` if the bits are not numbers.
+    pub fn range(&self) -> Option<(u32, u32)> {
+        let (msb, lsb) = self
+            .bits
+            .split_once(':')
+            .unwrap_or((&self.bits, &self.bits));
+        let msb = parse_number(msb)? as u32;
+        let lsb = parse_number(lsb)? as u32;
+        Some((msb.max(lsb), msb.min(lsb)))
+    }
+}
+
+/// Registers whose addresses overlap, given their offsets and widths.
+pub fn register_overlaps(registers: &[SvRegister]) -> Vec<String> {
+    let mut result = Vec::new();
+    let spans: Vec<(&SvRegister, Option<(u64, u64)>)> = registers
+        .iter()
+        .map(|x| {
+            let offset = x.offset.as_deref().and_then(parse_number);
+            (
+                x,
+                offset.map(|o| (o, o + (x.width as u64).div_ceil(8).max(1))),
+            )
+        })
+        .collect();
+    for (i, (register, span)) in spans.iter().enumerate() {
+        let Some((start, end)) = span else {
+            continue;
+        };
+        for (other, other_span) in &spans[..i] {
+            if let Some((other_start, other_end)) = other_span {
+                if start < other_end && other_start < end {
+                    result.push(format!(
+                        "register {} at {} overlaps {} at {}",
+                        register.name,
+                        register.offset.as_deref().unwrap_or_default(),
+                        other.name,
+                        other.offset.as_deref().unwrap_or_default()
+                    ));
+                }
+            }
         }
     }
+    result
+}
+
+/// Groups named by `@ingroup`, or else the innermost group opened with `@{`.
+fn entity_groups(items: &[CommentItem], open_groups: &[String]) -> Vec<String> {
+    let groups: Vec<String> = items
+        .iter()
+        .filter_map(|x| match x {
+            CommentItem::InGroup(x) => Some(x.split_whitespace().map(|x| x.to_string())),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    if groups.is_empty() {
+        open_groups.last().cloned().into_iter().collect()
+    } else {
+        groups
+    }
+}
+
+fn is_group_command(item: &CommentItem) -> bool {
+    matches!(
+        item,
+        CommentItem::DefGroup { .. }
+            | CommentItem::AddToGroup { .. }
+            | CommentItem::InGroup(_)
+            | CommentItem::GroupOpen
+            | CommentItem::GroupClose
+    )
 }
 
 trait Refine {
@@ -157,42 +942,68 @@ impl Refine for SvModule {
             signals,
             tasks,
             state_machines,
+            kind,
+            groups,
+            typedefs,
+            instances,
             ..
         } = self;
 
         let mut brief = Vec::new();
         let mut ports = ports.clone();
         let mut params = params.clone();
-        let mut comments = Vec::new();
         for c in comment {
             match c {
                 CommentItem::Brief(s) => brief.push(s.clone()),
-                CommentItem::Port { name, desc } => {
-                    if let Some(p) = ports.iter_mut().find(|x| &x.name == name) {
+                CommentItem::Port { name, desc } if !is_pattern(name) => {
+                    for p in ports.iter_mut().filter(|x| matches_names(name, &x.name)) {
                         p.comment = desc.clone();
                     }
                 }
-                CommentItem::Param { name, desc } => {
-                    if let Some(p) = params.iter_mut().find(|x| &x.name == name) {
+                CommentItem::Param { name, desc } if !is_pattern(name) => {
+                    for p in params.iter_mut().filter(|x| matches_names(name, &x.name)) {
                         p.comment = desc.clone();
                     }
                 }
-                _ => comments.push(c),
+                CommentItem::Custom {
+                    tag,
+                    name: Some(name),
+                    desc,
+                } => {
+                    for p in ports.iter_mut().filter(|x| matches_names(name, &x.name)) {
+                        p.tags.push((tag.clone(), desc.clone()));
+                    }
+                }
+                _ => (),
             }
         }
+        describe_by_patterns(comment, &mut ports, &mut params);
+        let mut lifecycle = Lifecycle::default();
+        let comment = refine_lifecycle(comment, &mut lifecycle, &mut ports, &mut params);
+        let comment = refine_clocking(&comment, &mut ports);
+        let comment = refine_reqs(&comment, &mut ports);
+        let (registers, comment) = SvRegister::from_items(&comment);
+        let (port_groups, comment) = port_groups(&comment, &ports);
         SvModule {
             name: name.clone(),
-            brief: if brief.len() == 0 {
+            brief: if brief.is_empty() {
                 None
             } else {
                 Some(brief.join("\n"))
             },
             ports: ports.clone(),
             params: params.clone(),
-            comment: comment.clone(),
+            comment,
             signals: signals.clone(),
             state_machines: state_machines.clone(),
             tasks: tasks.clone(),
+            lifecycle,
+            kind: *kind,
+            groups: groups.clone(),
+            typedefs: typedefs.clone(),
+            instances: instances.clone(),
+            registers,
+            port_groups,
         }
     }
 }
@@ -206,41 +1017,57 @@ impl Refine for SvFunctionTask {
             comment,
             signals,
             is_function,
+            groups,
             ..
         } = self;
 
         let mut brief = Vec::new();
         let mut ports = ports.clone();
         let mut params = params.clone();
-        let mut comments = Vec::new();
         for c in comment {
             match c {
                 CommentItem::Brief(s) => brief.push(s.clone()),
-                CommentItem::Port { name, desc } => {
-                    if let Some(p) = ports.iter_mut().find(|x| &x.name == name) {
+                CommentItem::Port { name, desc } if !is_pattern(name) => {
+                    for p in ports.iter_mut().filter(|x| matches_names(name, &x.name)) {
                         p.comment = desc.clone();
                     }
                 }
-                CommentItem::Param { name, desc } => {
-                    if let Some(p) = params.iter_mut().find(|x| &x.name == name) {
+                CommentItem::Param { name, desc } if !is_pattern(name) => {
+                    for p in params.iter_mut().filter(|x| matches_names(name, &x.name)) {
                         p.comment = desc.clone();
                     }
                 }
-                _ => comments.push(c),
+                CommentItem::Custom {
+                    tag,
+                    name: Some(name),
+                    desc,
+                } => {
+                    for p in ports.iter_mut().filter(|x| matches_names(name, &x.name)) {
+                        p.tags.push((tag.clone(), desc.clone()));
+                    }
+                }
+                _ => (),
             }
         }
+        describe_by_patterns(comment, &mut ports, &mut params);
+        let mut lifecycle = Lifecycle::default();
+        let comment = refine_lifecycle(comment, &mut lifecycle, &mut ports, &mut params);
+        let comment = refine_clocking(&comment, &mut ports);
+        let comment = refine_reqs(&comment, &mut ports);
         SvFunctionTask {
             name: name.clone(),
-            brief: if brief.len() == 0 {
+            brief: if brief.is_empty() {
                 None
             } else {
                 Some(brief.join("\n"))
             },
             ports: ports.clone(),
             params: params.clone(),
-            comment: comment.clone(),
+            comment,
             signals: signals.clone(),
             is_function: *is_function,
+            lifecycle,
+            groups: groups.clone(),
         }
     }
 }
@@ -251,6 +1078,172 @@ impl Refine for SvFile {
     }
 }
 
+/// Whether `names`, a comma-separated list of names and glob patterns, includes `name`.
+fn matches_names(names: &str, name: &str) -> bool {
+    names
+        .split(',')
+        .any(|x| glob_match(x.trim().as_bytes(), name.as_bytes()))
+}
+
+/// Whether the glob `pattern` with `*` and `?` wildcards matches all of `name`.
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, _) => name.is_empty(),
+        (Some(b'*'), _) => {
+            glob_match(&pattern[1..], name) || (!name.is_empty() && glob_match(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => glob_match(&pattern[1..], &name[1..]),
+        (Some(x), Some(y)) if x == y => glob_match(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+/// Whether `names` of a `@port` or `@param` contains glob patterns.
+fn is_pattern(names: &str) -> bool {
+    names.contains(['*', '?'])
+}
+
+/// Applies the `@port` and `@param` descriptions given by glob patterns to the ports and params
+/// not documented otherwise, the last matching pattern taking priority.
+fn describe_by_patterns(comment: &[CommentItem], ports: &mut [SvPort], params: &mut [SvParam]) {
+    for item in comment.iter().rev() {
+        match item {
+            CommentItem::Port { name, desc } if is_pattern(name) => {
+                for p in ports
+                    .iter_mut()
+                    .filter(|x| x.comment.is_empty() && matches_names(name, &x.name))
+                {
+                    p.comment = desc.clone();
+                }
+            }
+            CommentItem::Param { name, desc } if is_pattern(name) => {
+                for p in params
+                    .iter_mut()
+                    .filter(|x| x.comment.is_empty() && matches_names(name, &x.name))
+                {
+                    p.comment = desc.clone();
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// `text` without `//` and `/* */` comments outside of string literals.
+fn strip_comments(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find(['"', '/']) {
+        let (code, tail) = rest.split_at(i);
+        result.push_str(code);
+        if let Some(literal) = tail.strip_prefix('"') {
+            let end = literal.find('"').map_or(tail.len(), |x| x + 2);
+            result.push_str(&tail[..end]);
+            rest = &tail[end..];
+        } else if tail.starts_with("//") {
+            rest = tail.find('\n').map_or("", |x| &tail[x..]);
+        } else if tail.starts_with("/*") {
+            rest = tail.find("*/").map_or("", |x| &tail[x + 2..]);
+        } else {
+            result.push('/');
+            rest = &tail[1..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Kind of the declaration a `///<` comment documents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Declared {
+    Port,
+    Param,
+    Typedef,
+}
+
+/// Adjacent `///` or `//!` lines read so far, merged into one comment.
+struct LineBlock {
+    text: String,
+    /// Length of the pending items before the block.
+    pending: usize,
+    /// Offset of the end of the last line.
+    end: usize,
+    file: String,
+    line: usize,
+}
+
+/// Documents the declaration preceding a `///<` comment in the innermost entity.
+/// Returns false when there is no such declaration.
+fn document_declaration(
+    doc_stack: &mut [DocStackable],
+    declared: &Option<(Declared, String)>,
+    items: &Vec<CommentItem>,
+) -> bool {
+    let Some((kind, name)) = declared else {
+        return false;
+    };
+    let brief = items.get_brief();
+    let document =
+        |comment: &mut String, lifecycle: &mut Lifecycle, open: &mut Vec<CommentItem>| {
+            *comment = brief.clone();
+            let mut new = Lifecycle::from_items(items);
+            new.merge(lifecycle.clone());
+            *lifecycle = new;
+            open.extend(open_items(items));
+        };
+    let (ports, params) = match doc_stack.last_mut() {
+        Some(DocStackable::Module(m)) => {
+            if *kind == Declared::Typedef {
+                return match m.typedefs.iter_mut().find(|x| &x.name == name) {
+                    Some(typedef) => {
+                        typedef.comment = brief;
+                        true
+                    }
+                    None => false,
+                };
+            }
+            (&mut m.ports, &mut m.params)
+        }
+        Some(DocStackable::Task(t)) => (&mut t.ports, &mut t.params),
+        None => return false,
+    };
+    match kind {
+        Declared::Port => ports
+            .iter_mut()
+            .find(|x| &x.name == name)
+            .map(|x| {
+                document(&mut x.comment, &mut x.lifecycle, &mut x.open);
+                for item in items {
+                    if let CommentItem::Req { name: None, ids } = item {
+                        x.reqs.extend(requirement_ids(ids).map(|x| x.to_string()));
+                    }
+                }
+                let (mut clocking, desc) = Clocking::from_items(items);
+                clocking.merge(x.clocking.clone());
+                x.clocking = clocking;
+                if x.comment.is_empty() {
+                    x.comment = desc;
+                }
+            })
+            .is_some(),
+        Declared::Param => params
+            .iter_mut()
+            .find(|x| &x.name == name)
+            .map(|x| document(&mut x.comment, &mut x.lifecycle, &mut x.open))
+            .is_some(),
+        Declared::Typedef => false,
+    }
+}
+
+/// The `@todo`, `@bug` and `@fixme` items of `items`.
+fn open_items(items: &[CommentItem]) -> Vec<CommentItem> {
+    items
+        .iter()
+        .filter(|x| matches!(x, CommentItem::Open { .. }))
+        .cloned()
+        .collect()
+}
+
 trait GetBrief {
     fn get_brief(&self) -> String;
 }
@@ -260,6 +1253,9 @@ impl GetBrief for Vec<CommentItem> {
             .iter()
             .filter_map(|x| match x {
                 CommentItem::Brief(s) => Some(s.clone()),
+                // resolved by `resolve_copydoc`
+                CommentItem::CopyDoc(s) => Some(format!("@copydoc {}", s.trim())),
+                CommentItem::CopyBrief(s) => Some(format!("@copybrief {}", s.trim())),
                 _ => None,
             })
             .collect();
@@ -267,79 +1263,431 @@ impl GetBrief for Vec<CommentItem> {
     }
 }
 
+#[derive(Debug)]
+pub enum DocgenError {
+    Io {
+        file: String,
+        source: std::io::Error,
+    },
+    Parse {
+        file: String,
+        line: usize,
+        column: usize,
+    },
+    MissingInclude {
+        include: String,
+        chain: Vec<String>,
+    },
+    /// An included file that is not UTF-8; sv-parser reads included files itself, as UTF-8.
+    IncludeEncoding {
+        include: String,
+        chain: Vec<String>,
+    },
+    Sv {
+        file: String,
+        source: sv_parser::Error,
+    },
+}
+
+impl fmt::Display for DocgenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DocgenError::Io { file, source } => write!(f, "unable to open {}: {}", file, source),
+            DocgenError::Parse { file, line, column } => {
+                write!(f, "{}:{}:{}: parse error", file, line, column)
+            }
+            DocgenError::MissingInclude { include, chain } => write!(
+                f,
+                "cannot find include file \"{}\" (included from {})",
+                include,
+                chain.join(" <- ")
+            ),
+            DocgenError::IncludeEncoding { include, chain } => write!(
+                f,
+                "included file {} is not valid UTF-8 (included from {}); only the files given on \
+                 the command line can use other encodings",
+                include,
+                chain.join(" <- ")
+            ),
+            DocgenError::Sv { file, source } => write!(f, "{}: {}", file, source),
+        }
+    }
+}
+
+impl Error for DocgenError {}
+
+/// Decodes `bytes` to UTF-8. Without an explicit `encoding`, a BOM is honoured, valid UTF-8 is
+/// kept as is and anything else is decoded with the detected legacy encoding (GBK, Latin-1, ...).
+/// Returns the text, the encoding used and whether malformed sequences were replaced.
+pub fn decode_source(
+    bytes: &[u8],
+    encoding: Option<&'static Encoding>,
+) -> (String, &'static Encoding, bool) {
+    let encoding = match encoding {
+        Some(x) => x,
+        None => {
+            if let Some((x, _)) = Encoding::for_bom(bytes) {
+                x
+            } else if std::str::from_utf8(bytes).is_ok() {
+                UTF_8
+            } else {
+                let mut detector = EncodingDetector::new();
+                detector.feed(bytes, true);
+                detector.guess(None, true)
+            }
+        }
+    };
+    let (text, used, had_errors) = encoding.decode(bytes);
+    (text.into_owned(), used, had_errors)
+}
+
+/// 1-based line and column (in characters) of the byte `offset` in `text`.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(text.len());
+    let before = text.get(..offset).unwrap_or(text);
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    (line, column)
+}
+
+/// File names of `` `include "x" `` and `` `include <x> `` directives in `text`.
+fn scan_includes(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|x| {
+            let x = x.split("//").next().unwrap_or("").trim();
+            let x = x.strip_prefix("`include")?.trim();
+            let x = x
+                .strip_prefix('"')
+                .and_then(|x| x.split('"').next())
+                .or_else(|| x.strip_prefix('<').and_then(|x| x.split('>').next()))?;
+            Some(x.to_string())
+        })
+        .collect()
+}
+
+/// Looks up an included file the way sv-parser does: relative to the working directory, then
+/// in `include_dirs` in order.
+fn resolve_include(include: &str, include_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let include = Path::new(include);
+    if include.exists() || include.is_absolute() {
+        return Some(include.to_path_buf()).filter(|x| x.exists());
+    }
+    include_dirs
+        .iter()
+        .map(|x| x.join(include))
+        .find(|x| x.exists())
+}
+
+/// Text of an included file as sv-parser reads it: UTF-8, including a BOM.
+fn included_text(path: &Path) -> String {
+    String::from_utf8_lossy(&fs::read(path).unwrap_or_default()).into_owned()
+}
+
+/// Files included by a source file, with the include chains (innermost first) of the files
+/// that could not be found or are not UTF-8.
+#[derive(Default)]
+struct IncludeGraph {
+    /// `(including file, included file)` pairs.
+    edges: Vec<(String, String)>,
+    /// Directory of the source file, then those of all included files.
+    dirs: Vec<PathBuf>,
+    missing: Vec<(String, Vec<String>)>,
+    not_utf8: Vec<(String, Vec<String>)>,
+}
+
+/// Adds the files included by `file` to `graph`, following nested includes.
+fn collect_includes(
+    file: &Path,
+    text: &str,
+    include_dirs: &[PathBuf],
+    chain: &mut Vec<String>,
+    graph: &mut IncludeGraph,
+) {
+    chain.push(file.to_string_lossy().to_string());
+    for include in scan_includes(text) {
+        match resolve_include(include.as_str(), include_dirs) {
+            Some(path) => {
+                let name = path.to_string_lossy().to_string();
+                graph
+                    .edges
+                    .push((file.to_string_lossy().to_string(), name.clone()));
+                if chain.contains(&name) {
+                    continue;
+                }
+                if let Some(dir) = path
+                    .parent()
+                    .filter(|x| !graph.dirs.contains(&x.to_path_buf()))
+                {
+                    graph.dirs.push(dir.to_path_buf());
+                }
+                if let Ok(bytes) = fs::read(&path) {
+                    if std::str::from_utf8(&bytes).is_err() {
+                        let chain = chain.iter().rev().cloned().collect();
+                        graph.not_utf8.push((name, chain));
+                        continue;
+                    }
+                    let content = included_text(&path);
+                    collect_includes(&path, &content, include_dirs, chain, graph);
+                }
+            }
+            None => graph
+                .missing
+                .push((include, chain.iter().rev().cloned().collect())),
+        }
+    }
+    chain.pop();
+}
+
 impl Docgen {
-    pub fn from_file<'a>(
+    pub fn from_file(
         file: &str,
         defines: &HashMap<String, Option<Define>>,
-        includes: &Vec<PathBuf>,
-    ) -> Result<Docgen, impl Error> {
-        let content = fs::read_to_string(file).expect(&format!("unable to open {}", file));
+        includes: &[PathBuf],
+        encoding: Option<&'static Encoding>,
+    ) -> Result<Docgen, DocgenError> {
+        let bytes = fs::read(file).map_err(|source| DocgenError::Io {
+            file: file.to_string(),
+            source,
+        })?;
+        let (content, used, had_errors) = decode_source(&bytes, encoding);
+        if had_errors {
+            eprintln!(
+                "warning: {} contains characters that are not valid {}",
+                file,
+                used.name()
+            );
+        }
         Self::new(content.as_str(), file, defines, includes)
     }
 
-    pub fn new<'a>(
+    pub fn new(
         verilog: &str,
         file: &str,
         defines: &HashMap<String, Option<Define>>,
-        includes: &Vec<PathBuf>,
-    ) -> Result<Docgen, impl Error> {
-        let parsed = parse_sv_str(
-            &verilog,
-            &PathBuf::from(""),
-            &defines,
-            &includes,
-            false,
-            false,
-        );
+        includes: &[PathBuf],
+    ) -> Result<Docgen, DocgenError> {
+        let path = PathBuf::from(file);
+        // sv-parser searches the directories of the source file and all included files before
+        // the include paths; collect until the directories found match those searched
+        let mut dirs = vec![path.parent().unwrap_or(Path::new("")).to_path_buf()];
+        let (graph, include_dirs) = loop {
+            let include_dirs: Vec<PathBuf> = dirs.iter().chain(includes).cloned().collect();
+            let mut graph = IncludeGraph {
+                dirs: dirs.clone(),
+                ..Default::default()
+            };
+            collect_includes(&path, verilog, &include_dirs, &mut Vec::new(), &mut graph);
+            if graph.dirs == dirs {
+                break (graph, include_dirs);
+            }
+            dirs = graph.dirs;
+        };
+
+        let parsed = parse_sv_str(verilog, &path, defines, &include_dirs, false, false);
         match parsed {
             Ok((syntax_tree, _defines)) => Ok(Docgen {
                 file: file.to_string(),
+                source: verilog.to_string(),
                 tree: syntax_tree,
+                lang: None,
+                includes: graph.edges,
+                tags: Vec::new(),
+                autobrief: false,
+            }),
+            Err(sv_parser::Error::Parse(Some((origin, offset)))) => {
+                let (line, column) = if origin == path {
+                    line_column(verilog, offset)
+                } else {
+                    line_column(included_text(&origin).as_str(), offset)
+                };
+                Err(DocgenError::Parse {
+                    file: origin.to_string_lossy().to_string(),
+                    line,
+                    column,
+                })
+            }
+            Err(x @ sv_parser::Error::Include { .. }) => {
+                let mut source = &x;
+                while let sv_parser::Error::Include { source: inner } = source {
+                    source = inner.as_ref();
+                }
+                match source {
+                    sv_parser::Error::File { path, .. } => {
+                        let mut missing = graph.missing.into_iter();
+                        if let Some((include, chain)) =
+                            missing.find(|(include, _)| path.ends_with(include))
+                        {
+                            return Err(DocgenError::MissingInclude { include, chain });
+                        }
+                    }
+                    sv_parser::Error::Io(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                        if let Some((include, chain)) = graph.not_utf8.into_iter().next() {
+                            return Err(DocgenError::IncludeEncoding { include, chain });
+                        }
+                    }
+                    _ => (),
+                }
+                Err(DocgenError::Sv {
+                    file: file.to_string(),
+                    source: x,
+                })
+            }
+            Err(x) => Err(DocgenError::Sv {
+                file: file.to_string(),
+                source: x,
             }),
-            Err(x) => Err(x),
         }
     }
 
+    /// Selects the `@cmd[lang]` variants used in the parsed documentation.
+    pub fn with_lang(mut self, lang: Option<String>) -> Self {
+        self.lang = lang;
+        self
+    }
+
+    /// Recognises the custom tags declared in the configuration.
+    pub fn with_tags(mut self, tags: Vec<CustomTag>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Takes the first sentence of the documentation as brief when `@brief` is not given.
+    pub fn with_autobrief(mut self, autobrief: bool) -> Self {
+        self.autobrief = autobrief;
+        self
+    }
+
     pub fn parse_tree(&self) -> SvFile {
         let mut result = SvFile::new(self.file.as_str());
+        result.includes = self.includes.clone();
         let mut doc_stack: Vec<DocStackable> = vec![];
         let mut pending_items: Vec<CommentItem> = vec![];
+        // groups opened with `@{`
+        let mut open_groups: Vec<String> = vec![];
+        // the declaration being parsed, and the last one documented by a following `///<`
+        let mut declaring: Option<Declared> = None;
+        let mut last_declared: Option<(Declared, String)> = None;
+        let mut typedef_name: Option<String> = None;
+        let mut line_block: Option<LineBlock> = None;
 
         for event in self.tree.into_iter().event() {
+            match &event {
+                NodeEvent::Enter(
+                    RefNode::ModuleDeclaration(_)
+                    | RefNode::InterfaceDeclaration(_)
+                    | RefNode::PackageDeclaration(_)
+                    | RefNode::FunctionDeclaration(_)
+                    | RefNode::TaskDeclaration(_),
+                ) => last_declared = None,
+                NodeEvent::Enter(
+                    RefNode::AnsiPortDeclaration(_)
+                    | RefNode::PortDeclaration(_)
+                    | RefNode::TfPortItem(_)
+                    | RefNode::TfPortDeclaration(_),
+                ) => declaring = Some(Declared::Port),
+                NodeEvent::Enter(RefNode::TypeDeclaration(x)) => {
+                    // members of a struct are not documented
+                    declaring = Some(Declared::Typedef);
+                    last_declared = None;
+                    typedef_name = match x {
+                        TypeDeclaration::DataType(x) => Some(self.get_str(&x.nodes.2)),
+                        _ => None,
+                    };
+                }
+                NodeEvent::Enter(RefNode::TypeIdentifier(x))
+                    if declaring == Some(Declared::Typedef) =>
+                {
+                    let name = self.get_str(*x);
+                    if typedef_name.as_ref() == Some(&name) {
+                        last_declared = Some((Declared::Typedef, name));
+                    }
+                }
+                NodeEvent::Enter(RefNode::PortIdentifier(x))
+                    if declaring == Some(Declared::Port) =>
+                {
+                    last_declared = Some((Declared::Port, self.get_str(*x)))
+                }
+                NodeEvent::Enter(RefNode::VariableIdentifier(x))
+                    if declaring == Some(Declared::Port) =>
+                {
+                    last_declared = Some((Declared::Port, self.get_str(*x)))
+                }
+                NodeEvent::Enter(RefNode::ParamAssignment(x)) => {
+                    last_declared = Some((Declared::Param, self.get_str(&x.nodes.0)))
+                }
+                NodeEvent::Leave(
+                    RefNode::AnsiPortDeclaration(_)
+                    | RefNode::PortDeclaration(_)
+                    | RefNode::TfPortItem(_)
+                    | RefNode::TfPortDeclaration(_),
+                ) => declaring = None,
+                NodeEvent::Leave(RefNode::TypeDeclaration(_)) => declaring = None,
+                _ => (),
+            }
             match event {
                 NodeEvent::Enter(node) => match node {
-                    RefNode::ModuleDeclaration(_) => {
+                    RefNode::ModuleDeclaration(_)
+                    | RefNode::InterfaceDeclaration(_)
+                    | RefNode::PackageDeclaration(_) => {
                         let name = self.get_identifier(&node).unwrap();
                         let mut module = SvModule::new(name.as_str());
-                        module.comment = pending_items;
+                        module.kind = match node {
+                            RefNode::InterfaceDeclaration(_) => ModuleKind::Interface,
+                            RefNode::PackageDeclaration(_) => ModuleKind::Package,
+                            _ => ModuleKind::Module,
+                        };
+                        module.comment =
+                            self.entity_comment(pending_items, TagScope::Module, &name);
+                        module.groups = entity_groups(&module.comment, &open_groups);
                         doc_stack.push(DocStackable::Module(module));
                         pending_items = vec![];
                     }
                     RefNode::FunctionDeclaration(_) => {
                         let name = self.get_identifier(&node).unwrap();
                         let mut functask = SvFunctionTask::new(name.as_str(), true);
-                        functask.comment = pending_items;
+                        functask.comment =
+                            self.entity_comment(pending_items, TagScope::Function, &name);
+                        functask.groups = entity_groups(&functask.comment, &[]);
                         doc_stack.push(DocStackable::Task(functask));
                         pending_items = vec![];
                     }
                     RefNode::TaskDeclaration(_) => {
                         let name = self.get_identifier(&node).unwrap();
                         let mut functask = SvFunctionTask::new(name.as_str(), false);
-                        functask.comment = pending_items;
+                        functask.comment =
+                            self.entity_comment(pending_items, TagScope::Function, &name);
+                        functask.groups = entity_groups(&functask.comment, &[]);
                         doc_stack.push(DocStackable::Task(functask));
                         pending_items = vec![];
                     }
                     RefNode::AnsiPortDeclaration(x) => {
+                        // a port without a header continues the previous declaration, and one
+                        // without a direction inherits it unless it is an interface port
+                        let headless = match x {
+                            AnsiPortDeclaration::Net(x) => x.nodes.0.is_none(),
+                            AnsiPortDeclaration::Variable(x) => x.nodes.0.is_none(),
+                            AnsiPortDeclaration::Paren(x) => x.nodes.0.is_none(),
+                        };
+                        let interface = matches!(
+                            x,
+                            AnsiPortDeclaration::Net(x) if matches!(
+                                x.nodes.0,
+                                Some(NetPortHeaderOrInterfacePortHeader::InterfacePortHeader(_))
+                            )
+                        );
                         let port = match x {
                             AnsiPortDeclaration::Net(x) => {
-                                let header = x.nodes.0.as_ref().and_then(|x| match x {
-                                    NetPortHeaderOrInterfacePortHeader::NetPortHeader(y) => Some(y),
-                                    _ => None,
-                                });
-                                let direction = header
-                                    .and_then(|x| x.nodes.0.as_ref())
-                                    .map(|x| self.get_str(x));
-                                let port_type = header.map(|x| self.get_str(&x.nodes.1));
+                                let (direction, port_type) = match &x.nodes.0 {
+                                    Some(NetPortHeaderOrInterfacePortHeader::NetPortHeader(y)) => (
+                                        y.nodes.0.as_ref().map(|x| self.get_str(x)),
+                                        Some(self.get_str(&y.nodes.1)),
+                                    ),
+                                    // `bus_if.slave s` has the interface as its type
+                                    Some(
+                                        NetPortHeaderOrInterfacePortHeader::InterfacePortHeader(y),
+                                    ) => (None, Some(self.get_str(y.as_ref()))),
+                                    None => (None, None),
+                                };
                                 let dimensions = Some(
                                     x.nodes
                                         .2
@@ -353,6 +1701,12 @@ impl Docgen {
                                     direction,
                                     dimensions,
                                     comment: "".to_string(),
+                                    tags: Vec::new(),
+                                    lifecycle: Lifecycle::default(),
+                                    inherited: None,
+                                    clocking: Clocking::default(),
+                                    reqs: Vec::new(),
+                                    open: Vec::new(),
                                 }
                             }
                             AnsiPortDeclaration::Variable(x) => {
@@ -375,6 +1729,12 @@ impl Docgen {
                                     direction,
                                     dimensions,
                                     comment: "".to_string(),
+                                    tags: Vec::new(),
+                                    lifecycle: Lifecycle::default(),
+                                    inherited: None,
+                                    clocking: Clocking::default(),
+                                    reqs: Vec::new(),
+                                    open: Vec::new(),
                                 }
                             }
                             AnsiPortDeclaration::Paren(x) => {
@@ -385,20 +1745,31 @@ impl Docgen {
                                     direction,
                                     dimensions: None,
                                     comment: "".to_string(),
+                                    tags: Vec::new(),
+                                    lifecycle: Lifecycle::default(),
+                                    inherited: None,
+                                    clocking: Clocking::default(),
+                                    reqs: Vec::new(),
+                                    open: Vec::new(),
                                 }
                             }
                         };
 
-                        if let Some(item) = doc_stack
+                        if let Some(DocStackable::Module(m)) = doc_stack
                             .iter_mut()
                             .rfind(|x| matches!(x, DocStackable::Module { .. }))
                         {
-                            match item {
-                                DocStackable::Module(m) => {
-                                    m.ports.push(port);
+                            // ANSI ports without direction/type inherit them from the previous port
+                            let mut port = port;
+                            if let Some(last) = m.ports.last() {
+                                if port.direction.is_none() && !interface {
+                                    port.direction = last.direction.clone();
+                                }
+                                if headless {
+                                    port.port_type = last.port_type.clone();
                                 }
-                                _ => (),
                             }
+                            m.ports.push(port);
                         }
                     }
                     RefNode::PortDeclaration(x) => {
@@ -495,16 +1866,19 @@ impl Docgen {
                                         direction: Some(direction.clone()),
                                         dimensions: Some(dimentions[i].clone()),
                                         comment: "".to_string(),
+                                        tags: Vec::new(),
+                                        lifecycle: Lifecycle::default(),
+                                        inherited: None,
+                                        clocking: Clocking::default(),
+                                        reqs: Vec::new(),
+                                        open: Vec::new(),
                                     })
                                     .collect();
-                                if let Some(item) = doc_stack
+                                if let Some(DocStackable::Module(m)) = doc_stack
                                     .iter_mut()
                                     .rfind(|x| matches!(x, DocStackable::Module { .. }))
                                 {
-                                    match item {
-                                        DocStackable::Module(m) => m.ports.append(&mut new_ports),
-                                        _ => (),
-                                    }
+                                    m.ports.append(&mut new_ports);
                                 }
                             }
                         }
@@ -523,6 +1897,12 @@ impl Docgen {
                                 direction: direction.clone(),
                                 dimensions: Some(dimensions),
                                 comment: String::new(),
+                                tags: Vec::new(),
+                                lifecycle: Lifecycle::default(),
+                                inherited: None,
+                                clocking: Clocking::default(),
+                                reqs: Vec::new(),
+                                open: Vec::new(),
                             };
                         } else {
                             // TODO: sv-parse error?
@@ -532,18 +1912,30 @@ impl Docgen {
                                 direction: direction.clone(),
                                 dimensions: None,
                                 comment: String::new(),
+                                tags: Vec::new(),
+                                lifecycle: Lifecycle::default(),
+                                inherited: None,
+                                clocking: Clocking::default(),
+                                reqs: Vec::new(),
+                                open: Vec::new(),
                             };
                         }
-                        if let Some(item) = doc_stack
+                        if let Some(DocStackable::Task(m)) = doc_stack
                             .iter_mut()
                             .rfind(|x| matches!(x, DocStackable::Task { .. }))
                         {
-                            match item {
-                                DocStackable::Task(m) => {
-                                    m.ports.push(port);
+                            // like ANSI ports, a port without direction inherits it, and
+                            // one without direction and type inherits both
+                            let mut port = port;
+                            if let Some(last) = m.ports.last() {
+                                if port.direction.is_none() {
+                                    if port.port_type.as_deref().unwrap_or_default().is_empty() {
+                                        port.port_type = last.port_type.clone();
+                                    }
+                                    port.direction = last.direction.clone();
                                 }
-                                _ => (),
                             }
+                            m.ports.push(port);
                         }
                     }
                     RefNode::TfPortDeclaration(x) => {
@@ -561,60 +1953,295 @@ impl Docgen {
                             .map(|x| (self.get_str(&x.1 .0), self.get_str(&x.1 .1)))
                             .collect::<Vec<(String, String)>>();
                         first.append(&mut others);
-                        if let Some(item) = doc_stack
+                        if let Some(DocStackable::Task(m)) = doc_stack
                             .iter_mut()
                             .rfind(|x| matches!(x, DocStackable::Task { .. }))
                         {
-                            match item {
-                                DocStackable::Task(m) => {
-                                    for (name, dimensions) in first {
-                                        let port = SvPort {
-                                            name,
-                                            port_type: Some(port_type.clone()),
-                                            direction: Some(direction.clone()),
-                                            dimensions: Some(dimensions),
-                                            comment: String::new(),
-                                        };
-                                        m.ports.push(port);
+                            for (name, dimensions) in first {
+                                let port = SvPort {
+                                    name,
+                                    port_type: Some(port_type.clone()),
+                                    direction: Some(direction.clone()),
+                                    dimensions: Some(dimensions),
+                                    comment: String::new(),
+                                    tags: Vec::new(),
+                                    lifecycle: Lifecycle::default(),
+                                    inherited: None,
+                                    clocking: Clocking::default(),
+                                    reqs: Vec::new(),
+                                    open: Vec::new(),
+                                };
+                                m.ports.push(port);
+                            }
+                        }
+                    }
+                    RefNode::TypeDeclaration(TypeDeclaration::DataType(x)) => {
+                        let definition = self.get_str(&x.nodes.1)
+                            + x.nodes
+                                .3
+                                .iter()
+                                .map(|x| self.get_str(x))
+                                .collect::<String>()
+                                .as_str();
+                        let typedef = SvTypedef {
+                            name: self.get_str(&x.nodes.2),
+                            definition: definition.split_whitespace().collect::<Vec<_>>().join(" "),
+                            comment: self.select_language(pending_items.clone()).get_brief(),
+                        };
+                        if let Some(DocStackable::Module(m)) = doc_stack
+                            .iter_mut()
+                            .rfind(|x| matches!(x, DocStackable::Module { .. }))
+                        {
+                            m.typedefs.push(typedef);
+                        }
+                        pending_items.clear();
+                    }
+                    RefNode::AlwaysConstruct(x) => {
+                        if let Some(DocStackable::Module(m)) = doc_stack
+                            .iter_mut()
+                            .rfind(|x| matches!(x, DocStackable::Module { .. }))
+                        {
+                            self.infer_clocking(x, &mut m.ports);
+                        }
+                    }
+                    RefNode::ModuleInstantiation(x) => {
+                        let module = self.get_str(&x.nodes.0);
+                        let mut parameters = Vec::new();
+                        match x.nodes.1.as_ref().and_then(|x| x.nodes.1.nodes.1.as_ref()) {
+                            Some(ListOfParameterAssignments::Ordered(x)) => {
+                                for param in x.nodes.0.contents() {
+                                    parameters.push((None, self.get_str(param)));
+                                }
+                            }
+                            Some(ListOfParameterAssignments::Named(x)) => {
+                                for param in x.nodes.0.contents() {
+                                    let expression = match &param.nodes.2.nodes.1 {
+                                        Some(x) => self.get_str(x),
+                                        None => String::new(),
+                                    };
+                                    parameters
+                                        .push((Some(self.get_str(&param.nodes.1)), expression));
+                                }
+                            }
+                            None => (),
+                        }
+                        if let Some(DocStackable::Module(m)) = doc_stack
+                            .iter_mut()
+                            .rfind(|x| matches!(x, DocStackable::Module { .. }))
+                        {
+                            for instance in x.nodes.2.contents() {
+                                let mut connections = Vec::new();
+                                let mut wildcard = false;
+                                match &instance.nodes.1.nodes.1 {
+                                    Some(ListOfPortConnections::Ordered(x)) => {
+                                        for port in x.nodes.0.contents() {
+                                            connections.push((None, self.get_str(&port.nodes.1)));
+                                        }
+                                    }
+                                    Some(ListOfPortConnections::Named(x)) => {
+                                        for port in x.nodes.0.contents() {
+                                            match port {
+                                                NamedPortConnection::Identifier(x) => {
+                                                    let name = self.get_str(&x.nodes.2);
+                                                    // `.din` connects the signal of the same name
+                                                    let expression = match &x.nodes.3 {
+                                                        Some(x) => self.get_str(&x.nodes.1),
+                                                        None => name.clone(),
+                                                    };
+                                                    connections.push((Some(name), expression));
+                                                }
+                                                NamedPortConnection::Asterisk(_) => wildcard = true,
+                                            }
+                                        }
                                     }
+                                    None => (),
                                 }
-                                _ => (),
+                                m.instances.push(SvInstance {
+                                    module: module.clone(),
+                                    name: self.get_str(&instance.nodes.0.nodes.0),
+                                    connections,
+                                    wildcard,
+                                    parameters: parameters.clone(),
+                                });
                             }
                         }
                     }
                     RefNode::Comment(x) => {
-                        let mut comment_items = parse_comment(self.get_str(x).as_str());
-                        pending_items.append(&mut comment_items);
+                        let text = self.get_str(x);
+                        let (file, first_line) = self.location(&x.nodes.0);
+                        // adjacent `///` lines form one comment, whose earlier lines are checked
+                        let (text, file, first_line, checked) = match line_block.take() {
+                            Some(block)
+                                if is_line_comment(&text)
+                                    && is_trailing_comment(&block.text)
+                                        == is_trailing_comment(&text)
+                                    && self.adjacent(block.end, x.nodes.0.offset) =>
+                            {
+                                pending_items.truncate(block.pending);
+                                let checked = block.text.lines().count();
+                                (block.text + "\n" + &text, block.file, block.line, checked)
+                            }
+                            _ => (text, file, first_line, 0),
+                        };
+                        for (line, name) in unknown_commands(&text, &self.tags) {
+                            if line > checked {
+                                eprintln!(
+                                    "warning: {}:{}: unknown command @{} is kept as text",
+                                    file,
+                                    first_line + line - 1,
+                                    name
+                                );
+                            }
+                        }
+                        if is_line_comment(&text) {
+                            line_block = Some(LineBlock {
+                                text: text.clone(),
+                                pending: pending_items.len(),
+                                end: x.nodes.0.offset + x.nodes.0.len,
+                                file: file.clone(),
+                                line: first_line,
+                            });
+                        }
+                        let mut comment_items =
+                            parse_comment_with_tags(text.as_str(), &self.tags, self.autobrief);
+                        for item in comment_items.iter_mut() {
+                            let item = match item {
+                                CommentItem::Lang { lang: _, item } => item.as_mut(),
+                                _ => item,
+                            };
+                            match item {
+                                CommentItem::Open {
+                                    file: f, line: l, ..
+                                }
+                                | CommentItem::Example {
+                                    file: f, line: l, ..
+                                } => {
+                                    *f = Some(file.clone());
+                                    *l += first_line - 1;
+                                }
+                                CommentItem::Image { path, .. } if !is_url(path) => {
+                                    // images are given relative to the documented source
+                                    let resolved = match Path::new(&file).parent() {
+                                        Some(dir) => dir.join(path.as_str()),
+                                        None => PathBuf::from(path.as_str()),
+                                    };
+                                    if !resolved.is_file() {
+                                        eprintln!(
+                                            "error: {}:{}: image {} not found",
+                                            file, first_line, path
+                                        );
+                                        result.missing_images.push(path.clone());
+                                    }
+                                    *path = resolved.to_string_lossy().to_string();
+                                }
+                                _ => (),
+                            }
+                        }
+                        if is_trailing_comment(&text) {
+                            let items = self.select_language(comment_items);
+                            if !document_declaration(&mut doc_stack, &last_declared, &items) {
+                                eprintln!(
+                                    "warning: {}:{}: no declaration precedes the trailing comment",
+                                    file, first_line
+                                );
+                            }
+                        } else if let Some((name, title)) =
+                            comment_items.iter().find_map(|x| match x {
+                                CommentItem::DefGroup { name, title }
+                                | CommentItem::AddToGroup { name, title } => {
+                                    Some((name.clone(), title.clone()))
+                                }
+                                _ => None,
+                            })
+                        {
+                            // the comment documents the group, not the next entity
+                            let items = self.select_language(comment_items);
+                            let parent = entity_groups(&items, &open_groups).into_iter().next();
+                            let group = match result.groups.iter_mut().find(|x| x.name == name) {
+                                Some(x) => x,
+                                None => {
+                                    result.groups.push(SvGroup {
+                                        name: name.clone(),
+                                        title: String::new(),
+                                        brief: None,
+                                        comment: Vec::new(),
+                                        parent: None,
+                                    });
+                                    result.groups.last_mut().unwrap()
+                                }
+                            };
+                            if group.title.is_empty() {
+                                group.title = title;
+                            }
+                            let brief = items.get_brief();
+                            if !brief.is_empty() {
+                                group.brief = Some(brief);
+                            }
+                            if group.parent.is_none() && parent.as_ref() != Some(&name) {
+                                group.parent = parent;
+                            }
+                            group
+                                .comment
+                                .extend(items.iter().filter(|x| !is_group_command(x)).cloned());
+                            if items.contains(&CommentItem::GroupOpen) {
+                                open_groups.push(name);
+                            }
+                        } else {
+                            if comment_items.contains(&CommentItem::GroupClose) {
+                                open_groups.pop();
+                            }
+                            comment_items.retain(|x| {
+                                !matches!(x, CommentItem::GroupOpen | CommentItem::GroupClose)
+                            });
+                            pending_items.append(&mut comment_items);
+                        }
                     }
                     RefNode::ParameterPortList(x) => {
-                        let assignments: Vec<&ParamAssignment> = x
+                        // `(name, dimensions, default, type)` of each assignment
+                        let assignments: Vec<_> = x
                             .into_iter()
                             .filter_map(|x| match x {
-                                RefNode::ParamAssignment(y) => Some(y),
+                                RefNode::ParamAssignment(y) => Some((
+                                    self.get_str(&y.nodes.0),
+                                    Some(self.get_str(&y.nodes.1)),
+                                    y.nodes.2.as_ref().map(|x| self.get_str(&x.1)),
+                                    None,
+                                )),
+                                // `parameter type T = logic`
+                                RefNode::TypeAssignment(y) => Some((
+                                    self.get_str(&y.nodes.0),
+                                    None,
+                                    y.nodes.1.as_ref().map(|x| self.get_str(&x.1)),
+                                    Some("type".to_string()),
+                                )),
                                 _ => None,
                             })
                             .collect();
+                        let items = self.select_language(pending_items.clone());
                         let mut new_params: Vec<SvParam> = assignments
-                            .iter()
-                            .map(|x| SvParam {
-                                name: self.get_str(&x.nodes.0),
-                                dimensions: Some(self.get_str(&x.nodes.1)),
-                                default: x.nodes.2.as_ref().map(|x| self.get_str(&x.1)),
-                                param_type: None,
-                                comment: pending_items.get_brief(),
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, (name, dimensions, default, param_type))| SvParam {
+                                name,
+                                dimensions,
+                                default,
+                                param_type,
+                                comment: items.get_brief(),
+                                lifecycle: Lifecycle::from_items(&items),
+                                // reported once for the whole declaration
+                                open: if i == 0 {
+                                    open_items(&items)
+                                } else {
+                                    Vec::new()
+                                },
                             })
                             .collect();
 
-                        if let Some(item) = doc_stack
+                        if let Some(DocStackable::Module(m)) = doc_stack
                             .iter_mut()
                             .rfind(|x| matches!(x, DocStackable::Module { .. }))
                         {
-                            match item {
-                                DocStackable::Module(m) => {
-                                    m.params.append(&mut new_params);
-                                }
-                                _ => (),
-                            }
+                            m.params.append(&mut new_params);
                         }
                         pending_items.clear();
                     }
@@ -629,26 +2256,43 @@ impl Docgen {
                         let mut assignments: Vec<&ParamAssignment> =
                             x.nodes.2.nodes.0.nodes.1.iter().map(|x| &x.1).collect();
                         assignment0.append(&mut assignments);
-                        let mut new_params: Vec<SvParam> = assignment0
+                        let items = self.select_language(pending_items.clone());
+                        let new_params: Vec<SvParam> = assignment0
                             .iter()
-                            .map(|x| SvParam {
+                            .enumerate()
+                            .map(|(i, x)| SvParam {
                                 name: self.get_str(&x.nodes.0),
                                 dimensions: Some(self.get_str(&x.nodes.1)),
                                 default: x.nodes.2.as_ref().map(|x| self.get_str(&x.1)),
                                 param_type: param_type.clone(),
-                                comment: pending_items.get_brief(),
+                                comment: items.get_brief(),
+                                lifecycle: Lifecycle::from_items(&items),
+                                open: if i == 0 {
+                                    open_items(&items)
+                                } else {
+                                    Vec::new()
+                                },
                             })
                             .collect();
 
-                        if let Some(item) = doc_stack
+                        if let Some(DocStackable::Module(m)) = doc_stack
                             .iter_mut()
                             .rfind(|x| matches!(x, DocStackable::Module { .. }))
                         {
-                            match item {
-                                DocStackable::Module(m) => {
-                                    m.params.append(&mut new_params);
+                            for param in new_params {
+                                // `#(parameter W=8)` is already collected by its ParameterPortList
+                                if let Some(p) = m.params.iter_mut().find(|x| x.name == param.name)
+                                {
+                                    if param.param_type.is_some() {
+                                        p.param_type = param.param_type;
+                                    }
+                                    if p.comment.is_empty() {
+                                        p.comment = param.comment;
+                                    }
+                                    p.lifecycle.merge(param.lifecycle);
+                                } else {
+                                    m.params.push(param);
                                 }
-                                _ => (),
                             }
                         }
                         pending_items.clear();
@@ -658,38 +2302,56 @@ impl Docgen {
                     }
                 },
                 NodeEvent::Leave(node) => match node {
-                    RefNode::ModuleDeclaration(_) => {
+                    RefNode::ModuleDeclaration(_)
+                    | RefNode::InterfaceDeclaration(_)
+                    | RefNode::PackageDeclaration(_) => {
                         if let Some(DocStackable::Module(d)) = doc_stack.pop() {
-                            result.modules.push(d.refine());
+                            let mut module = d.refine();
+                            let problems = module
+                                .registers
+                                .iter()
+                                .flat_map(|x| x.problems())
+                                .chain(register_overlaps(&module.registers))
+                                .chain(
+                                    module
+                                        .port_groups
+                                        .iter()
+                                        .filter(|x| x.ports.is_empty())
+                                        .map(|x| format!("port group {} has no ports", x.name)),
+                                );
+                            for problem in problems {
+                                eprintln!(
+                                    "warning: {}: module {}: {}",
+                                    self.file, module.name, problem
+                                );
+                            }
+                            module.port_groups.retain(|x| !x.ports.is_empty());
+                            for register in module.registers.iter_mut() {
+                                let width = register.width;
+                                register
+                                    .fields
+                                    .retain(|x| x.range().is_none_or(|x| x.0 < width));
+                            }
+                            result.modules.push(module);
                         }
                     }
                     RefNode::FunctionDeclaration(_) => {
                         if let Some(DocStackable::Task(d)) = &doc_stack.pop() {
-                            if let Some(item) = doc_stack
+                            if let Some(DocStackable::Module(m)) = doc_stack
                                 .iter_mut()
                                 .rfind(|x| matches!(x, DocStackable::Module { .. }))
                             {
-                                match item {
-                                    DocStackable::Module(m) => {
-                                        m.tasks.push(d.clone().refine());
-                                    }
-                                    _ => (),
-                                }
+                                m.tasks.push(d.clone().refine());
                             }
                         }
                     }
                     RefNode::TaskDeclaration(_) => {
                         if let Some(DocStackable::Task(d)) = &doc_stack.pop() {
-                            if let Some(item) = doc_stack
+                            if let Some(DocStackable::Module(m)) = doc_stack
                                 .iter_mut()
                                 .rfind(|x| matches!(x, DocStackable::Module { .. }))
                             {
-                                match item {
-                                    DocStackable::Module(m) => {
-                                        m.tasks.push(d.clone().refine());
-                                    }
-                                    _ => (),
-                                }
+                                m.tasks.push(d.clone().refine());
                             }
                         }
                     }
@@ -701,6 +2363,69 @@ impl Docgen {
         result.refine()
     }
 
+    /// File and line of `locate` in the original sources.
+    fn location(&self, locate: &Locate) -> (String, usize) {
+        match self.tree.get_origin(locate) {
+            Some((origin, offset)) if origin == Path::new(&self.file) => {
+                (self.file.clone(), line_column(&self.source, offset).0)
+            }
+            Some((origin, offset)) => {
+                let text = included_text(origin);
+                (
+                    origin.to_string_lossy().to_string(),
+                    line_column(&text, offset).0,
+                )
+            }
+            None => (self.file.clone(), locate.line as usize),
+        }
+    }
+
+    /// Whether only spaces and at most one line break separate the offsets.
+    fn adjacent(&self, end: usize, start: usize) -> bool {
+        if start < end {
+            return false;
+        }
+        let between = Locate {
+            offset: end,
+            line: 0,
+            len: start - end,
+        };
+        self.tree
+            .get_str(&between)
+            .is_some_and(|x| x.trim().is_empty() && x.matches('\n').count() <= 1)
+    }
+
+    fn select_language(&self, items: Vec<CommentItem>) -> Vec<CommentItem> {
+        select_language(items, self.lang.as_deref())
+    }
+
+    /// Documentation of a module or function/task, without custom tags that may not document it.
+    fn entity_comment(
+        &self,
+        items: Vec<CommentItem>,
+        scope: TagScope,
+        entity: &str,
+    ) -> Vec<CommentItem> {
+        self.select_language(items)
+            .into_iter()
+            .filter(|x| match x {
+                CommentItem::Custom { tag, name, .. } => {
+                    let allowed = self.tags.iter().find(|t| &t.name == tag).is_none_or(|t| {
+                        t.allows(scope) || (name.is_some() && t.allows(TagScope::Port))
+                    });
+                    if !allowed {
+                        eprintln!(
+                            "warning: {}: @{} is not allowed to document {:?} {}",
+                            self.file, tag, scope, entity
+                        );
+                    }
+                    allowed
+                }
+                _ => true,
+            })
+            .collect()
+    }
+
     fn get_identifier(&self, node: &RefNode) -> Option<String> {
         let mut location: Option<Locate> = None;
         for x in node.clone().into_iter() {
@@ -723,7 +2448,495 @@ impl Docgen {
         }
     }
 
+    /// Infers the clock, the asynchronous resets and the ports synchronous to the clock of
+    /// an `always_ff @(posedge clk or negedge rst_n)` block, keeping annotated clocking. The
+    /// clock is the edge not tested by the first `if` of the block, or not named like a reset.
+    fn infer_clocking(&self, always: &AlwaysConstruct, ports: &mut [SvPort]) {
+        if !matches!(always.nodes.0, AlwaysKeyword::AlwaysFf(_)) {
+            return;
+        }
+        let mut edges = Vec::new();
+        let mut names = HashSet::new();
+        let mut tested: Option<HashSet<String>> = None;
+        for node in always {
+            match node {
+                RefNode::EventExpressionExpression(x) => {
+                    if let Some(edge) = &x.nodes.0 {
+                        edges.push((self.get_str(&x.nodes.1), self.get_str(edge) == "negedge"));
+                    }
+                }
+                RefNode::ConditionalStatement(x) if tested.is_none() => {
+                    let condition = RefNode::CondPredicate(&x.nodes.2.nodes.1);
+                    let identifiers = condition.into_iter().filter_map(|x| match x {
+                        RefNode::SimpleIdentifier(x) => Some(self.get_str(x)),
+                        _ => None,
+                    });
+                    tested = Some(identifiers.collect());
+                }
+                RefNode::SimpleIdentifier(x) => {
+                    names.insert(self.get_str(x));
+                }
+                _ => (),
+            }
+        }
+        let tested = tested.unwrap_or_default();
+        let untested: Vec<_> = edges.iter().filter(|x| !tested.contains(&x.0)).collect();
+        let candidates = if untested.is_empty() {
+            edges.iter().collect()
+        } else {
+            untested
+        };
+        let like_reset = |name: &str| {
+            let name = name.to_lowercase();
+            name.contains("rst") || name.contains("reset")
+        };
+        // the other edges are asynchronous resets
+        let Some((clock, _)) = candidates
+            .iter()
+            .find(|x| !like_reset(&x.0))
+            .or(candidates.first())
+            .copied()
+        else {
+            return;
+        };
+        let resets: Vec<_> = edges.iter().filter(|x| &x.0 != clock).collect();
+        for port in ports.iter_mut() {
+            let inferred = if &port.name == clock {
+                Clocking {
+                    clock: true,
+                    ..Default::default()
+                }
+            } else if let Some((_, negedge)) = resets.iter().find(|x| x.0 == port.name) {
+                Clocking {
+                    reset: Some(SvReset {
+                        active_low: Some(*negedge),
+                        asynchronous: Some(true),
+                    }),
+                    domain: Some(clock.clone()),
+                    ..Default::default()
+                }
+            } else if names.contains(&port.name) && !port.clocking.clock {
+                Clocking {
+                    domain: Some(clock.clone()),
+                    ..Default::default()
+                }
+            } else {
+                continue;
+            };
+            port.clocking.merge(inferred);
+        }
+    }
+
+    /// Text of `node`, without the comments following its tokens unless it is a comment.
     fn get_str<'a, T: Into<RefNodes<'a>>>(&self, node: T) -> String {
-        self.tree.get_str(node).unwrap_or("").trim().to_string()
+        let text = self.tree.get_str(node).unwrap_or("").trim();
+        if text.starts_with("//") || text.starts_with("/*") {
+            text.to_string()
+        } else {
+            strip_comments(text).trim().to_string()
+        }
+    }
+}
+
+#[test]
+fn test_parse_ports_and_params() {
+    let input = "module m #(parameter W = 8) (input wire [W-1:0] a, b, output c, bus_if.slave s);
+    parameter W = 8;
+    function f (input [3:0] x, y, output z);
+    endfunction
+endmodule
+";
+    let file = Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let module = &file.modules[0];
+    let directions: Vec<_> = module
+        .ports
+        .iter()
+        .map(|x| (x.name.as_str(), x.direction.as_deref()))
+        .collect();
+    assert_eq!(
+        directions,
+        vec![
+            ("a", Some("input")),
+            ("b", Some("input")),
+            ("c", Some("output")),
+            // an interface port has no direction
+            ("s", None),
+        ]
+    );
+    // `b` takes the direction and type of `a`
+    assert_eq!(module.ports[1].port_type, module.ports[0].port_type);
+    assert_eq!(module.ports[3].port_type.as_deref(), Some("bus_if.slave"));
+    // function ports inherit likewise
+    let ports: Vec<_> = module.tasks[0]
+        .ports
+        .iter()
+        .map(|x| {
+            (
+                x.name.as_str(),
+                x.direction.as_deref(),
+                x.port_type.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        ports,
+        vec![
+            ("x", Some("input"), Some("[3:0]")),
+            ("y", Some("input"), Some("[3:0]")),
+            ("z", Some("output"), None),
+        ]
+    );
+    // the body declaration of `W` is the header parameter
+    assert_eq!(module.params.len(), 1);
+}
+
+/// Directory `name` under the temporary directory, created empty for file fixtures.
+#[cfg(test)]
+fn fixture_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("sv-docgen-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_decode_source() {
+    let gbk = Encoding::for_label(b"gbk").unwrap();
+    let text = "// 这是一个时钟分频模块，输出时钟的频率是输入时钟的一半\nmodule m; endmodule\n";
+    let (bytes, _, _) = gbk.encode(text);
+    assert_eq!(decode_source(&bytes, Some(gbk)).0, text);
+    // detected without `--encoding`
+    let (decoded, used, _) = decode_source(&bytes, None);
+    assert_eq!((decoded.as_str(), used.name()), (text, "GBK"));
+
+    let latin1 = Encoding::for_label(b"latin1").unwrap();
+    let bytes = b"// caf\xe9\nmodule m; endmodule\n";
+    assert_eq!(
+        decode_source(bytes, Some(latin1)).0,
+        "// caf\u{e9}\nmodule m; endmodule\n"
+    );
+}
+
+#[test]
+fn test_encoding_error_location() {
+    let dir = fixture_dir("encoding");
+    let gbk = Encoding::for_label(b"gbk").unwrap();
+    let file = dir.join("gbk.sv");
+    let (bytes, _, _) = gbk.encode("// 中文注释\n/* 中文 */ module m(; endmodule\n");
+    fs::write(&file, &bytes).unwrap();
+    let file = file.to_string_lossy().to_string();
+    let error = Docgen::from_file(&file, &HashMap::new(), &[], Some(gbk)).err();
+    // the column counts characters of the original file
+    assert!(matches!(
+        error,
+        Some(DocgenError::Parse {
+            line: 2,
+            column: 19,
+            ..
+        })
+    ));
+
+    let latin1 = dir.join("latin1.svh");
+    fs::write(&latin1, b"// caf\xe9\n").unwrap();
+    let top = dir.join("top.sv");
+    fs::write(&top, "`include \"latin1.svh\"\nmodule m; endmodule\n").unwrap();
+    let top = top.to_string_lossy().to_string();
+    match Docgen::from_file(&top, &HashMap::new(), &[], None) {
+        Err(DocgenError::IncludeEncoding { include, chain }) => {
+            assert!(include.ends_with("latin1.svh"));
+            assert_eq!(chain, vec![top]);
+        }
+        _ => panic!("a Latin-1 include is reported"),
     }
 }
+
+#[test]
+fn test_image_path() {
+    let dir = fixture_dir("image");
+    fs::create_dir_all(dir.join("rtl/doc")).unwrap();
+    fs::write(dir.join("rtl/doc/timing.svg"), "<svg/>").unwrap();
+    let file = dir.join("rtl/m.sv");
+    fs::write(
+        &file,
+        "/**\n * @image doc/timing.svg \"Timing\"\n * @image https://example.com/x.png\n * @image doc/gone.svg\n */\nmodule m; endmodule\n",
+    )
+    .unwrap();
+    let file = file.to_string_lossy().to_string();
+    let parsed = Docgen::from_file(&file, &HashMap::new(), &[], None)
+        .unwrap()
+        .parse_tree();
+    let paths: Vec<String> = parsed.modules[0]
+        .comment
+        .iter()
+        .filter_map(|x| match x {
+            CommentItem::Image { path, .. } => Some(path.clone()),
+            _ => None,
+        })
+        .collect();
+    // relative to the source file rather than the working directory
+    let image = dir.join("rtl/doc/timing.svg").to_string_lossy().to_string();
+    let gone = dir.join("rtl/doc/gone.svg").to_string_lossy().to_string();
+    assert_eq!(
+        paths,
+        vec![image, "https://example.com/x.png".to_string(), gone]
+    );
+    // a missing local image is an error, a URL is not checked
+    assert_eq!(parsed.missing_images, vec!["doc/gone.svg".to_string()]);
+}
+
+#[test]
+fn test_include_search_order() {
+    let dir = fixture_dir("include");
+    fs::create_dir_all(dir.join("rtl")).unwrap();
+    fs::create_dir_all(dir.join("inc/sub")).unwrap();
+    // `a.svh` next to the including file wins over the one in the include path
+    fs::write(dir.join("rtl/a.svh"), "`include \"b.svh\"\n").unwrap();
+    fs::write(dir.join("inc/a.svh"), "`define WRONG\n").unwrap();
+    // `b.svh` is found in the include path, `sub/c.svh` next to `b.svh`
+    fs::write(dir.join("inc/b.svh"), "`include \"sub/c.svh\"\n").unwrap();
+    fs::write(dir.join("inc/sub/c.svh"), "`define WIDTH 8\n").unwrap();
+    let top = dir.join("rtl/top.sv");
+    fs::write(
+        &top,
+        "`include \"a.svh\"\nmodule m (input [`WIDTH-1:0] d);\nendmodule\n",
+    )
+    .unwrap();
+    let name = |x: &str| dir.join(x).to_string_lossy().to_string();
+    let includes = vec![dir.join("inc")];
+    let parsed = Docgen::from_file(&name("rtl/top.sv"), &HashMap::new(), &includes, None)
+        .unwrap()
+        .parse_tree();
+    assert_eq!(
+        parsed.includes,
+        vec![
+            (name("rtl/top.sv"), name("rtl/a.svh")),
+            (name("rtl/a.svh"), name("inc/b.svh")),
+            (name("inc/b.svh"), name("inc/sub/c.svh")),
+        ]
+    );
+
+    // the message names the whole chain, innermost first
+    fs::write(dir.join("inc/sub/c.svh"), "`include \"missing.svh\"\n").unwrap();
+    let error = Docgen::from_file(&name("rtl/top.sv"), &HashMap::new(), &includes, None)
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "cannot find include file \"missing.svh\" (included from {} <- {} <- {} <- {})",
+            name("inc/sub/c.svh"),
+            name("inc/b.svh"),
+            name("rtl/a.svh"),
+            name("rtl/top.sv")
+        )
+    );
+
+    // like sv-parser, a path relative to the working directory wins over the including
+    // file's directory
+    let cwd_dir = PathBuf::from(format!("target/sv-docgen-include-{}", std::process::id()));
+    fs::create_dir_all(&cwd_dir).unwrap();
+    fs::create_dir_all(dir.join("rtl").join(&cwd_dir)).unwrap();
+    let w = cwd_dir.join("w.svh");
+    fs::write(&w, "`define W 4\n").unwrap();
+    fs::write(dir.join("rtl").join(&w), "`define W 2\n").unwrap();
+    fs::write(
+        &top,
+        format!(
+            "`include \"{}\"\nmodule m (input [`W-1:0] d);\nendmodule\n",
+            w.display()
+        ),
+    )
+    .unwrap();
+    let parsed = Docgen::from_file(&name("rtl/top.sv"), &HashMap::new(), &includes, None)
+        .unwrap()
+        .parse_tree();
+    fs::remove_dir_all(&cwd_dir).unwrap();
+    assert_eq!(
+        parsed.includes,
+        vec![(name("rtl/top.sv"), w.to_string_lossy().to_string())]
+    );
+    assert_eq!(
+        parsed.modules[0].ports[0].port_type.as_deref(),
+        Some("[4 -1:0]")
+    );
+}
+
+#[test]
+fn test_infer_clocking() {
+    let input =
+        "module m (input rst_n, input clk, input arst, input wclk, input d, input e, input f);
+    always_ff @(negedge rst_n or posedge clk)
+        if (!rst_n) q <= 1'b0;
+        else q <= d;
+    always_ff @(posedge arst or posedge wclk) r <= e;
+    always @(posedge f) s <= d;
+endmodule
+";
+    let file = Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let clocking: Vec<_> = file.modules[0]
+        .ports
+        .iter()
+        .map(|x| {
+            (
+                x.name.as_str(),
+                x.clocking.clock,
+                x.clocking.reset,
+                x.clocking.domain.as_deref(),
+            )
+        })
+        .collect();
+    let reset = |active_low| {
+        Some(SvReset {
+            active_low: Some(active_low),
+            asynchronous: Some(true),
+        })
+    };
+    assert_eq!(
+        clocking,
+        vec![
+            // the reset listed first is tested by the leading `if`
+            ("rst_n", false, reset(true), Some("clk")),
+            ("clk", true, None, None),
+            // without `if`, the edge named like a reset is not the clock
+            ("arst", false, reset(false), Some("wclk")),
+            ("wclk", true, None, None),
+            ("d", false, None, Some("clk")),
+            ("e", false, None, Some("wclk")),
+            // a plain `always` block is not inferred from
+            ("f", false, None, None),
+        ]
+    );
+}
+
+#[test]
+fn test_register_problems() {
+    let input = "/**
+ * @reg CTRL offset=0x0 width=8
+ * @field mode[3:0]
+ * @field en[2]
+ * @field big[11:8]
+ * @field addr[ADDR_W-1:0]
+ * @reg STAT offset=0x0
+ */
+module m;
+endmodule
+";
+    let file = Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let registers = &file.modules[0].registers;
+    // the field outside of the register is left out, the others are kept
+    let fields: Vec<_> = registers[0]
+        .fields
+        .iter()
+        .map(|x| x.name.as_str())
+        .collect();
+    assert_eq!(fields, vec!["mode", "en", "addr"]);
+    let mut register = registers[0].clone();
+    register.fields.push(SvField {
+        name: "big".to_string(),
+        bits: "11:8".to_string(),
+        access: None,
+        reset: None,
+        desc: String::new(),
+    });
+    assert_eq!(
+        register.problems(),
+        vec![
+            "field en[2] of register CTRL overlaps mode[3:0]",
+            "field big[11:8] of register CTRL exceeds its 8 bits and is left out",
+        ]
+    );
+    assert_eq!(
+        register_overlaps(registers),
+        vec!["register STAT at 0x0 overlaps CTRL at 0x0"]
+    );
+}
+
+#[test]
+fn test_refine_lifecycle() {
+    let input = "/**
+ * @deprecated a_*: use b
+ * @since W: 1.2
+ * @deprecated Note: replaced by m2
+ */
+module m #(parameter W = 8) (
+    input a_0,
+    input a_1,
+    input b, ///< @deprecated Note: will be removed
+    input c
+);
+endmodule
+";
+    let file = Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let module = &file.modules[0];
+    let deprecated: Vec<_> = module
+        .ports
+        .iter()
+        .map(|x| (x.name.as_str(), x.lifecycle.deprecated.as_deref()))
+        .collect();
+    assert_eq!(
+        deprecated,
+        vec![
+            ("a_0", Some("use b")),
+            ("a_1", Some("use b")),
+            // `Note` names no port
+            ("b", Some("Note: will be removed")),
+            ("c", None),
+        ]
+    );
+    assert_eq!(module.params[0].lifecycle.since.as_deref(), Some("1.2"));
+    assert_eq!(
+        module.lifecycle.deprecated.as_deref(),
+        Some("Note: replaced by m2")
+    );
+    assert_eq!(module.lifecycle.since, None);
+}
+
+#[test]
+fn test_custom_tag_name_patterns() {
+    use crate::config::{TagScope, TagShape, TagStyle};
+
+    let tags = vec![CustomTag {
+        name: "timing".to_string(),
+        label: None,
+        shape: TagShape::Pair,
+        scope: vec![TagScope::Port],
+        style: TagStyle::Column,
+    }];
+    let input = "/**
+ * @timing a, b: registered
+ * @timing s_axi_*: AXI clock
+ */
+module m (input a, input b, input c, input s_axi_valid, input s_axi_ready);
+endmodule
+";
+    let parsed = Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .with_tags(tags)
+        .parse_tree();
+    let tags: Vec<(&str, Vec<&str>)> = parsed.modules[0]
+        .ports
+        .iter()
+        .map(|x| {
+            let descs = x.tags.iter().map(|x| x.1.trim()).collect();
+            (x.name.as_str(), descs)
+        })
+        .collect();
+    assert_eq!(
+        tags,
+        vec![
+            ("a", vec!["registered"]),
+            ("b", vec!["registered"]),
+            ("c", vec![]),
+            ("s_axi_valid", vec!["AXI clock"]),
+            ("s_axi_ready", vec!["AXI clock"]),
+        ]
+    );
+}
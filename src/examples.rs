@@ -0,0 +1,219 @@
+use crate::{
+    comment_parser::CommentItem,
+    docgen::{Docgen, DocgenError, SvFile, SvModule},
+};
+use std::{collections::HashMap, path::Path, path::PathBuf};
+use sv_parser::Define;
+
+/// Name of the module an `@example` body is wrapped in.
+const WRAPPER: &str = "sv_docgen_example";
+
+/// An `@example` of a module documentation with its location.
+struct Example<'a> {
+    file: String,
+    line: usize,
+    module: &'a str,
+    code: &'a str,
+}
+
+/// `@example`s of the modules of `files` written in SystemVerilog.
+fn examples(files: &[SvFile]) -> Vec<Example<'_>> {
+    let mut result = Vec::new();
+    for file in files {
+        for module in &file.modules {
+            for item in &module.comment {
+                if let CommentItem::Example {
+                    lang,
+                    code,
+                    file: f,
+                    line,
+                } = item
+                {
+                    let lang = lang.as_deref().unwrap_or("systemverilog");
+                    if matches!(lang, "systemverilog" | "sv" | "verilog" | "v") {
+                        result.push(Example {
+                            file: f.clone().unwrap_or_else(|| file.name.clone()),
+                            line: *line,
+                            module: &module.name,
+                            code,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Problems of `example`: a syntax error of its body wrapped in a module, or instantiations
+/// of the known `modules` connecting ports or params they don't have, by name or by position.
+fn check(
+    example: &Example,
+    modules: &[&SvModule],
+    defines: &HashMap<String, Option<Define>>,
+    includes: &[PathBuf],
+) -> Vec<String> {
+    let source = format!("module {};\n{}\nendmodule\n", WRAPPER, example.code);
+    let parsed = match Docgen::new(&source, &example.file, defines, includes) {
+        Ok(x) => x.parse_tree(),
+        Err(DocgenError::Parse { line, column, .. }) => {
+            // the first line is the wrapper
+            let line = line - 1;
+            if line > example.code.lines().count() {
+                return vec!["parse error at the end of the example".to_string()];
+            }
+            return vec![format!(
+                "parse error at line {}, column {} of the example",
+                line, column
+            )];
+        }
+        Err(e) => return vec![e.to_string()],
+    };
+    let mut result = Vec::new();
+    for instance in parsed.modules.iter().flat_map(|x| x.instances.iter()) {
+        let Some(module) = modules.iter().find(|x| x.name == instance.module) else {
+            continue;
+        };
+        let ordered = instance
+            .connections
+            .iter()
+            .filter(|x| x.0.is_none())
+            .count();
+        if ordered > module.ports.len() {
+            result.push(format!(
+                "{}: {} ports connected by position, {} has {}",
+                instance.name,
+                ordered,
+                module.name,
+                module.ports.len()
+            ));
+        }
+        let ordered = instance.parameters.iter().filter(|x| x.0.is_none()).count();
+        if ordered > module.params.len() {
+            result.push(format!(
+                "{}: {} parameters assigned by position, {} has {}",
+                instance.name,
+                ordered,
+                module.name,
+                module.params.len()
+            ));
+        }
+        for port in instance.connections.iter().filter_map(|x| x.0.as_ref()) {
+            if !module.ports.iter().any(|x| &x.name == port) {
+                result.push(format!(
+                    "{}: no port {} in {}",
+                    instance.name, port, module.name
+                ));
+            }
+        }
+        for param in instance.parameters.iter().filter_map(|x| x.0.as_ref()) {
+            if !module.params.iter().any(|x| &x.name == param) {
+                result.push(format!(
+                    "{}: no parameter {} in {}",
+                    instance.name, param, module.name
+                ));
+            }
+        }
+    }
+    result
+}
+
+/// Checks the `@example`s of the modules in `files` and reports each of them in the manner of
+/// doctests. Returns whether all examples passed.
+pub fn check_examples(
+    files: &[SvFile],
+    defines: &HashMap<String, Option<Define>>,
+    includes: &[PathBuf],
+) -> bool {
+    let modules: Vec<&SvModule> = files.iter().flat_map(|x| x.modules.iter()).collect();
+    let examples = examples(files);
+    let plural = if examples.len() == 1 { "" } else { "s" };
+    println!("running {} example{}", examples.len(), plural);
+    let mut failures = Vec::new();
+    for example in &examples {
+        let problems = check(example, &modules, defines, includes);
+        let name = Path::new(&example.file)
+            .file_name()
+            .and_then(|x| x.to_str())
+            .unwrap_or("");
+        let status = if problems.is_empty() { "ok" } else { "FAILED" };
+        println!(
+            "example {} - {} (line {}) ... {}",
+            name, example.module, example.line, status
+        );
+        if !problems.is_empty() {
+            failures.push((example, problems));
+        }
+    }
+    if !failures.is_empty() {
+        println!("\nfailures:");
+        for (example, problems) in &failures {
+            for problem in problems {
+                println!(
+                    "{}:{}: example of {}: {}",
+                    example.file, example.line, example.module, problem
+                );
+            }
+        }
+    }
+    println!(
+        "\nexample result: {}. {} passed; {} failed",
+        if failures.is_empty() { "ok" } else { "FAILED" },
+        examples.len() - failures.len(),
+        failures.len()
+    );
+    failures.is_empty()
+}
+
+#[test]
+fn test_check_examples() {
+    use crate::docgen::Docgen;
+
+    let input = "/**
+ * @example
+ * adder #(.W(8)) u_ok (.a(x), .b(y), .s(z));
+ * @endexample
+ * @example
+ * adder u_port (.a(x), .c(y));
+ * @endexample
+ * @example
+ * adder #(8, 1) u_order (x, y, z, w);
+ * @endexample
+ * @example
+ * adder u_parse (.a(x),
+ * @endexample
+ */
+module adder #(parameter W = 4) (
+    input [W-1:0] a,
+    input [W-1:0] b,
+    output [W-1:0] s
+);
+endmodule
+";
+    let files = vec![
+        Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+            .unwrap()
+            .parse_tree(),
+    ];
+    let modules: Vec<&SvModule> = files[0].modules.iter().collect();
+    let examples = examples(&files);
+    let problems: Vec<Vec<String>> = examples
+        .iter()
+        .map(|x| check(x, &modules, &HashMap::new(), &[]))
+        .collect();
+    assert_eq!(examples[0].line, 2);
+    assert!(problems[0].is_empty());
+    assert_eq!(problems[1], vec!["u_port: no port c in adder"]);
+    assert_eq!(
+        problems[2],
+        vec![
+            "u_order: 4 ports connected by position, adder has 3",
+            "u_order: 2 parameters assigned by position, adder has 1",
+        ]
+    );
+    assert_eq!(
+        problems[3],
+        vec!["parse error at line 1, column 21 of the example"]
+    );
+    assert!(!check_examples(&files, &HashMap::new(), &[]));
+}
@@ -1,4 +1,5 @@
 use std::{
+    cmp::Ordering,
     collections::hash_map::DefaultHasher,
     fs,
     hash::{Hash, Hasher},
@@ -6,7 +7,20 @@ use std::{
     process::Command,
 };
 
-use crate::{comment_parser::CommentItem, docgen::SvFile, numbered_list::NumberedList};
+use crate::{
+    comment_parser::{CommentItem, DiagramKind},
+    config::{Config, CustomTag, TagStyle},
+    docgen::{
+        parse_number, BusRole, Clocking, Lifecycle, SvFile, SvGroup, SvModule, SvOpenItem, SvParam,
+        SvPort, SvPortGroup, SvRegister, SvTrace, SvTypedef, TraceKind,
+    },
+    locale::Labels,
+    numbered_list::NumberedList,
+    symbols::{
+        inline_links, is_url, module_anchor, param_anchor, port_anchor, port_group_anchor,
+        register_anchor, task_anchor, type_anchor, Symbols,
+    },
+};
 
 pub trait DocgenGenerator {
     fn generate(&self, items: Vec<SvFile>) -> String;
@@ -16,63 +30,461 @@ pub struct MarkdownGenerator {
     pub cwd: String,
     pub wavedrom: Option<String>,
     pub graphviz: Option<String>,
+    pub plantuml: Option<String>,
+    pub labels: &'static Labels,
+    pub include_graph: bool,
+    pub config: Config,
+    /// Requirements `(id, title)` the traceability matrix reports the coverage of.
+    pub requirements: Vec<(String, String)>,
 }
 
 #[derive(Hash)]
-struct FSM {
+struct Fsm {
     name: String,
     transits: Vec<(String, String, String)>,
     states: Vec<(String, String)>,
 }
 
+/// Deprecated items and the version each item was added in, as `(kind, item, text)`.
+#[derive(Default)]
+struct VersionIndex {
+    deprecated: Vec<(String, String, String)>,
+    added: Vec<(String, String, String)>,
+}
+
+impl VersionIndex {
+    fn new(labels: &Labels, items: &[SvFile]) -> Self {
+        let mut result = VersionIndex::default();
+        for module in items.iter().flat_map(|x| x.modules.iter()) {
+            result.add(
+                labels.module_kind(module.kind),
+                &module.name,
+                &module.lifecycle,
+            );
+            result.add_params(labels.parameter, &module.name, &module.params);
+            result.add_ports(labels.port, &module.name, &module.ports);
+            for task in &module.tasks {
+                let task_name = format!("{}.{}", module.name, task.name);
+                let kind = if task.is_function {
+                    labels.function
+                } else {
+                    labels.task
+                };
+                result.add(kind, &task_name, &task.lifecycle);
+                result.add_params(labels.parameter, &task_name, &task.params);
+                result.add_ports(labels.port, &task_name, &task.ports);
+            }
+        }
+        result
+    }
+
+    fn add(&mut self, kind: &str, item: &str, lifecycle: &Lifecycle) {
+        if let Some(reason) = &lifecycle.deprecated {
+            self.deprecated
+                .push((kind.to_string(), item.to_string(), reason.clone()));
+        }
+        if let Some(since) = &lifecycle.since {
+            self.added
+                .push((kind.to_string(), item.to_string(), since.clone()));
+        }
+    }
+
+    fn add_ports(&mut self, kind: &str, parent: &str, ports: &[SvPort]) {
+        for port in ports {
+            self.add(
+                kind,
+                format!("{}.{}", parent, port.name).as_str(),
+                &port.lifecycle,
+            );
+        }
+    }
+
+    fn add_params(&mut self, kind: &str, parent: &str, params: &[SvParam]) {
+        for param in params {
+            self.add(
+                kind,
+                format!("{}.{}", parent, param.name).as_str(),
+                &param.lifecycle,
+            );
+        }
+    }
+}
+
+/// The brief of a module in the contents list, as `: brief` on one line.
+fn nav_brief(module: &SvModule) -> String {
+    match module
+        .brief
+        .as_deref()
+        .map(|x| x.split("\n\n").next().unwrap_or("").trim())
+    {
+        Some(brief) if !brief.is_empty() => {
+            format!(
+                ": {}",
+                brief.split_whitespace().collect::<Vec<_>>().join(" ")
+            )
+        }
+        _ => String::new(),
+    }
+}
+
+/// Markdown heading marks for `level`, which is at most 6.
+fn heading(level: usize) -> String {
+    "#".repeat(level.clamp(1, 6))
+}
+
+/// WaveDrom `reg` JSON of `(name, bits, attribute)` lanes given from LSB to MSB, unnamed
+/// lanes are reserved.
+fn wavedrom_reg(lanes: &[(Option<&str>, u32, Option<&str>)]) -> String {
+    let width: u32 = lanes.iter().map(|x| x.1).sum();
+    let lanes: Vec<String> = lanes
+        .iter()
+        .map(|(name, bits, attr)| {
+            let mut lane = format!("{{\"bits\": {}", bits);
+            if let Some(name) = name {
+                lane.push_str(format!(", \"name\": \"{}\"", name).as_str());
+            }
+            if let Some(attr) = attr {
+                lane.push_str(format!(", \"attr\": \"{}\"", attr).as_str());
+            }
+            lane.push('}');
+            lane
+        })
+        .collect();
+    format!(
+        "{{\"reg\": [{}], \"config\": {{\"bits\": {}}}}}",
+        lanes.join(", "),
+        width
+    )
+}
+
+/// Bitfield diagram of a register, `None` if its fields have no numeric or overlapping bits.
+fn register_bitfield(register: &SvRegister) -> Option<String> {
+    let mut fields = Vec::new();
+    for field in &register.fields {
+        fields.push((field.range()?, field));
+    }
+    fields.sort_by_key(|x| x.0 .1);
+    let mut lanes = Vec::new();
+    let mut next = 0;
+    for ((msb, lsb), field) in fields {
+        if lsb < next || msb >= register.width {
+            return None;
+        }
+        if lsb > next {
+            lanes.push((None, lsb - next, None));
+        }
+        lanes.push((
+            Some(field.name.as_str()),
+            msb - lsb + 1,
+            field.access.as_deref(),
+        ));
+        next = msb + 1;
+    }
+    if next < register.width {
+        lanes.push((None, register.width - next, None));
+    }
+    Some(wavedrom_reg(&lanes))
+}
+
+/// Bitfield diagram of a packed struct.
+fn struct_bitfield(typedef: &SvTypedef) -> Option<String> {
+    let fields = typedef.packed_fields()?;
+    let lanes: Vec<_> = fields
+        .iter()
+        .rev()
+        .map(|(name, bits)| (Some(name.as_str()), *bits, None))
+        .collect();
+    Some(wavedrom_reg(&lanes))
+}
+
+/// Groups of all files, merging the parts given by `@addtogroup`, and the groups
+/// only named by `@ingroup`.
+fn collect_groups(items: &[SvFile]) -> Vec<SvGroup> {
+    let mut groups: Vec<SvGroup> = Vec::new();
+    for group in items.iter().flat_map(|x| x.groups.iter()) {
+        match groups.iter_mut().find(|x| x.name == group.name) {
+            Some(x) => {
+                if x.title.is_empty() {
+                    x.title = group.title.clone();
+                }
+                if x.brief.is_none() {
+                    x.brief = group.brief.clone();
+                }
+                if x.parent.is_none() {
+                    x.parent = group.parent.clone();
+                }
+                x.comment.extend(group.comment.iter().cloned());
+            }
+            None => groups.push(group.clone()),
+        }
+    }
+    let referenced: Vec<String> = items
+        .iter()
+        .flat_map(|x| x.modules.iter())
+        .flat_map(|x| {
+            x.groups
+                .iter()
+                .chain(x.tasks.iter().flat_map(|y| y.groups.iter()))
+        })
+        .chain(groups.iter().filter_map(|x| x.parent.as_ref()))
+        .cloned()
+        .collect();
+    for name in referenced {
+        if !groups.iter().any(|x| x.name == name) {
+            groups.push(SvGroup {
+                name: name.clone(),
+                title: String::new(),
+                brief: None,
+                comment: Vec::new(),
+                parent: None,
+            });
+        }
+    }
+    for group in groups.iter_mut() {
+        if group.title.is_empty() {
+            group.title = group.name.clone();
+        }
+    }
+    groups
+}
+
+/// Groups of the document with the modules organized by them.
+struct GroupTree<'a> {
+    groups: Vec<SvGroup>,
+    roots: Vec<usize>,
+    children: Vec<Vec<usize>>,
+    modules: Vec<&'a SvModule>,
+}
+
+impl<'a> GroupTree<'a> {
+    fn new(items: &'a [SvFile]) -> Self {
+        let groups = collect_groups(items);
+        let (roots, children) = group_tree(&groups);
+        GroupTree {
+            groups,
+            roots,
+            children,
+            modules: items.iter().flat_map(|x| x.modules.iter()).collect(),
+        }
+    }
+}
+
+/// Roots and children of the group tree; groups whose parents form a cycle become roots.
+fn group_tree(groups: &[SvGroup]) -> (Vec<usize>, Vec<Vec<usize>>) {
+    let parent: Vec<Option<usize>> = groups
+        .iter()
+        .map(|x| {
+            x.parent
+                .as_ref()
+                .and_then(|p| groups.iter().position(|y| &y.name == p))
+        })
+        .collect();
+    let mut children = vec![Vec::new(); groups.len()];
+    let mut roots = Vec::new();
+    for (i, p) in parent.iter().enumerate() {
+        match p {
+            Some(p) => children[*p].push(i),
+            None => roots.push(i),
+        }
+    }
+    let mut placed = vec![false; groups.len()];
+    let mut stack = roots.clone();
+    loop {
+        while let Some(i) = stack.pop() {
+            placed[i] = true;
+            stack.extend(children[i].iter().filter(|x| !placed[**x]));
+        }
+        match placed.iter().position(|x| !x) {
+            Some(i) => {
+                if let Some(p) = parent[i] {
+                    children[p].retain(|x| *x != i);
+                }
+                roots.push(i);
+                stack.push(i);
+            }
+            None => break,
+        }
+    }
+    (roots, children)
+}
+
+/// Orders versions by their numeric parts, so that `1.10` comes after `1.9`.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let key = |v: &str| -> Vec<(Option<u64>, String)> {
+        v.split(['.', '-'])
+            .map(|x| (x.trim().parse::<u64>().ok(), x.to_string()))
+            .collect()
+    };
+    key(a).cmp(&key(b))
+}
+
+/// Markdown fenced code block, with a fence longer than any backtick run in `code`.
+fn fenced_code(lang: Option<&str>, code: &str) -> String {
+    let mut fence = String::from("```");
+    while code.contains(fence.as_str()) {
+        fence.push('`');
+    }
+    format!("{}{}\n{}\n{}\n", fence, lang.unwrap_or(""), code, fence)
+}
+
+fn hash_of<T: Hash + ?Sized>(x: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    x.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Markdown `text` as the content of a table cell: pipes are escaped and line breaks become `<br>`.
+fn table_cell(text: &str) -> String {
+    text.trim().replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// `@see target text` as an inline link to `target`, resolved with the other links.
+fn reference(s: &str) -> String {
+    let s = s.trim();
+    match s.split_once(char::is_whitespace) {
+        Some((target, text)) => format!("{{@link {}}} {}", target, text.trim()),
+        None => format!("{{@link {}}}", s),
+    }
+}
+
+/// Markdown `text` quoted as a block, keeping its paragraphs and lists.
+fn blockquote(text: &str) -> String {
+    text.lines()
+        .map(|x| {
+            if x.trim().is_empty() {
+                ">\n".to_string()
+            } else {
+                format!("> {}\n", x)
+            }
+        })
+        .collect()
+}
+
 impl MarkdownGenerator {
-    pub fn new(cwd: String, wavedrom: Option<String>, graphviz: Option<String>) -> Self {
+    pub fn new(
+        cwd: String,
+        wavedrom: Option<String>,
+        graphviz: Option<String>,
+        lang: Option<&str>,
+    ) -> Self {
         MarkdownGenerator {
             cwd,
             wavedrom,
             graphviz,
+            plantuml: None,
+            labels: Labels::for_lang(lang),
+            include_graph: false,
+            config: Config::default(),
+            requirements: Vec::new(),
         }
     }
 
     fn format_comment(&self, comments: &Vec<CommentItem>) -> String {
         let mut result = String::new();
-        let mut current_fsm: Option<FSM> = None;
+        let mut current_fsm: Option<Fsm> = None;
 
         for comment in comments {
             match comment {
                 CommentItem::Author(s) => {
-                    result.push_str(format!("**Author:** {}\n\n", s).as_str())
+                    result.push_str(format!("**{}:** {}\n\n", self.labels.author, s).as_str())
                 }
-                CommentItem::Example(s) => {
-                    if s.contains("\n") {
-                        result.push_str(format!("**Example:** \n```\n{}\n```\n\n", s).as_str());
-                    } else {
-                        result.push_str(format!("**Example:** `{}`\n\n", s).as_str());
+                CommentItem::Example { lang, code, .. } => {
+                    result.push_str(
+                        format!(
+                            "**{}:** \n{}\n",
+                            self.labels.example,
+                            fenced_code(lang.as_deref(), code)
+                        )
+                        .as_str(),
+                    );
+                }
+                CommentItem::Code { lang, code } => {
+                    result.push_str(format!("{}\n", fenced_code(lang.as_deref(), code)).as_str());
+                }
+                CommentItem::Details(s) => result.push_str(format!("{}\n\n", s).as_str()),
+                CommentItem::Image { path, caption } => {
+                    result.push_str(
+                        format!(
+                            "![{}]({})\n\n",
+                            caption.as_deref().unwrap_or("image"),
+                            self.copy_image(path)
+                        )
+                        .as_str(),
+                    );
+                    if let Some(caption) = caption {
+                        result.push_str(format!("*{}*\n\n", caption).as_str());
                     }
                 }
+                CommentItem::Diagram { kind, source } => {
+                    let diagram = self.generate_diagram(*kind, source);
+                    result.push_str(format!("{}\n\n", diagram.trim_end()).as_str());
+                }
                 CommentItem::Note(s) => {
                     if s.contains("\n") {
-                        result.push_str("> **Note:**\n>\n");
-                        s.split("\n")
-                            .map(|x| "> ".to_owned() + x + "\n>\n")
-                            .for_each(|x| result.push_str(x.as_str()));
-                        result.push_str("\n");
+                        result.push_str(format!("> **{}:**\n>\n", self.labels.note).as_str());
+                        result.push_str(blockquote(s).as_str());
+                        result.push('\n');
+                    } else {
+                        result.push_str(format!("> **{}:** {}\n\n", self.labels.note, s).as_str())
+                    }
+                }
+                CommentItem::Deprecated { desc, .. } => {
+                    if desc.is_empty() {
+                        result.push_str(format!("> **{}**\n\n", self.labels.deprecated).as_str());
                     } else {
-                        result.push_str(format!("> **Note:** {}\n\n", s).as_str())
+                        result.push_str(
+                            format!("> **{}:** {}\n\n", self.labels.deprecated, desc).as_str(),
+                        );
                     }
                 }
-                CommentItem::Ref(s) => {
-                    result.push_str(format!("**Ref:** [{}]({})\n\n", s, s).as_str())
+                CommentItem::Since { version, .. } => {
+                    result.push_str(format!("**{}:** {}\n\n", self.labels.since, version).as_str())
                 }
-                CommentItem::Return(s) => {
-                    result.push_str(format!("**Return:** {}\n\n", s).as_str())
+                CommentItem::Version { version, .. } => result
+                    .push_str(format!("**{}:** {}\n\n", self.labels.version, version).as_str()),
+                CommentItem::Open { kind, text, .. } => result.push_str(
+                    format!("> **{}:** {}\n\n", self.labels.open_kind(*kind), text).as_str(),
+                ),
+                CommentItem::Ref(s) => result.push_str(
+                    format!("**{}:** {}\n\n", self.labels.reference, reference(s)).as_str(),
+                ),
+                CommentItem::Custom { tag, name, desc } => {
+                    result.push_str(self.format_custom_tag(tag, name.as_deref(), desc).as_str())
                 }
-                CommentItem::See(s) => {
-                    result.push_str(format!("**Ref:** [{}]({})\n\n", s, s).as_str())
+                CommentItem::Return(s) => {
+                    result.push_str(format!("**{}:** {}\n\n", self.labels.ret, s).as_str())
                 }
+                CommentItem::See(s) => result.push_str(
+                    format!("**{}:** {}\n\n", self.labels.reference, reference(s)).as_str(),
+                ),
                 CommentItem::Wave(s) => {
                     result.push_str(
-                        format!("**Waveform:** \n\n {}\n\n", self.generate_waveform(s)).as_str(),
+                        format!(
+                            "**{}:** \n\n {}\n\n",
+                            self.labels.waveform,
+                            self.generate_waveform(s)
+                        )
+                        .as_str(),
+                    );
+                }
+                CommentItem::Bitfield(s) if self.wavedrom.is_none() => {
+                    result.push_str(
+                        format!(
+                            "**{}:** \n\n{}\n",
+                            self.labels.bitfield,
+                            fenced_code(Some("wavedrom"), s)
+                        )
+                        .as_str(),
+                    );
+                }
+                CommentItem::Bitfield(s) => {
+                    result.push_str(
+                        format!(
+                            "**{}:** \n\n {}\n\n",
+                            self.labels.bitfield,
+                            self.generate_waveform(s)
+                        )
+                        .as_str(),
                     );
                 }
                 CommentItem::State { name, desc } => {
@@ -89,14 +501,15 @@ impl MarkdownGenerator {
                     if let Some(fsm) = &current_fsm {
                         result.push_str(
                             format!(
-                                "**State Machine:** {}\n\n {}\n\n",
+                                "**{}:** {}\n\n {}\n\n",
+                                self.labels.state_machine,
                                 &fsm.name.clone(),
                                 &self.generate_fsm(fsm)
                             )
                             .as_str(),
                         );
                     }
-                    current_fsm = Some(FSM {
+                    current_fsm = Some(Fsm {
                         name: s.clone(),
                         states: Vec::new(),
                         transits: Vec::new(),
@@ -108,7 +521,8 @@ impl MarkdownGenerator {
         if let Some(fsm) = current_fsm {
             result.push_str(
                 format!(
-                    "**State Machine:** {}\n\n {}\n\n",
+                    "**{}:** {}\n\n {}\n\n",
+                    self.labels.state_machine,
                     &fsm.name.clone(),
                     &self.generate_fsm(&fsm)
                 )
@@ -118,221 +532,1576 @@ impl MarkdownGenerator {
         result
     }
 
-    fn generate_fsm(&self, fsm: &FSM) -> String {
-        if let Some(graphviz) = &self.graphviz {
-            let mut gv = String::from("digraph G {\n");
+    /// Name of an item, struck through and marked with a badge when deprecated.
+    fn format_name(&self, name: &str, lifecycle: &Lifecycle) -> String {
+        if lifecycle.deprecated.is_some() {
+            format!("~~{}~~ **[{}]**", name, self.labels.deprecated)
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// Brief of a port or param, followed by its version information.
+    fn format_brief(&self, brief: &str, lifecycle: &Lifecycle) -> String {
+        let l = self.labels;
+        let mut lines = vec![brief.trim().to_string()];
+        if let Some(reason) = &lifecycle.deprecated {
+            lines.push(format!("**{}:** {}", l.deprecated, reason));
+        }
+        if let Some(since) = &lifecycle.since {
+            lines.push(format!("**{}:** {}", l.since, since));
+        }
+        if let Some(version) = &lifecycle.version {
+            lines.push(format!("**{}:** {}", l.version, version));
+        }
+        lines.retain(|x| !x.is_empty());
+        lines.join("\n")
+    }
 
-            let mut hasher = DefaultHasher::new();
-            fsm.hash(&mut hasher);
-            let hash = hasher.finish();
-            let file_name = format!("docgen_fsm_{}.png", hash);
-            let file_path = Path::new(&self.cwd).join(&file_name);
-            let temp_file = Path::new(&self.cwd).join(".temp.gv");
+    /// Name cell of a table, with the anchor of the entity if it is referenced.
+    fn anchored(&self, name: String, anchor: Option<String>, symbols: &Symbols) -> String {
+        match anchor {
+            Some(anchor) if symbols.is_anchored(&anchor) => {
+                format!("<a id=\"{}\"></a>{}", anchor, name)
+            }
+            _ => name,
+        }
+    }
 
-            for (from, to, desc) in &fsm.transits {
-                gv.push_str(format!("{}->{}[label=\"{}\"]\n", from, to, desc).as_str());
+    /// Replaces the `{@link ...}` outside of code blocks by links, flagging unresolved targets.
+    fn resolve_links(&self, text: &str, symbols: &Symbols, scope: Option<&str>) -> String {
+        let mut result = String::new();
+        let mut code = false;
+        for line in text.split_inclusive('\n') {
+            if line.trim_start().starts_with("```") {
+                code = !code;
             }
+            if code {
+                result.push_str(line);
+                continue;
+            }
+            let mut last = 0;
+            for (start, end, target, label) in inline_links(line) {
+                result.push_str(&line[last..start]);
+                let label = label.unwrap_or(target);
+                if is_url(target) {
+                    result.push_str(format!("[{}]({})", label, target).as_str());
+                } else if let Some(symbol) = symbols.resolve(target, scope) {
+                    result.push_str(format!("[{}](#{})", label, symbol.anchor).as_str());
+                } else {
+                    eprintln!(
+                        "warning: unresolved reference {} in {}",
+                        target,
+                        scope.unwrap_or("group documentation")
+                    );
+                    result.push_str(format!("`{}` *({})*", label, self.labels.unresolved).as_str());
+                }
+                last = end;
+            }
+            result.push_str(&line[last..]);
+        }
+        result
+    }
 
-            if fsm.states.len() > 0 {
-                let s = fsm
-                    .states
-                    .iter()
-                    .map(|(state, desc)| format!("{{ {} | {} }}", state, desc))
-                    .collect::<Vec<String>>()
-                    .join("|");
-                gv.push_str(
+    fn format_typedefs(&self, typedefs: &[SvTypedef], symbols: &Symbols, module: &str) -> String {
+        let l = self.labels;
+        let mut result = String::new();
+        result.push_str(format!("| {} | {} | {} |\n", l.name, l.definition, l.brief).as_str());
+        result.push_str("| ---- | ---------- | ----- |\n");
+        for typedef in typedefs {
+            let name = self.anchored(
+                typedef.name.clone(),
+                Some(type_anchor(module, &typedef.name)),
+                symbols,
+            );
+            result.push_str(
+                format!(
+                    "| {} | `{}` | {} |\n",
+                    table_cell(&name),
+                    table_cell(&typedef.definition),
+                    table_cell(&typedef.comment)
+                )
+                .as_str(),
+            );
+        }
+        result.push('\n');
+        for typedef in typedefs {
+            result.push_str(
+                self.generate_bitfield(&typedef.name, struct_bitfield(typedef))
+                    .as_str(),
+            );
+        }
+        result
+    }
+
+    /// Address map of the registers followed by the fields of each register.
+    fn format_registers(
+        &self,
+        registers: &[SvRegister],
+        index: &mut NumberedList,
+        level: usize,
+        symbols: &Symbols,
+        module: &str,
+    ) -> String {
+        let l = self.labels;
+        let mut result = String::new();
+        let mut sorted: Vec<&SvRegister> = registers.iter().collect();
+        // registers without a numeric offset keep their place at the end
+        sorted.sort_by_key(|x| {
+            x.offset
+                .as_deref()
+                .and_then(parse_number)
+                .unwrap_or(u64::MAX)
+        });
+        result.push_str(
+            format!(
+                "| {} | {} | {} | {} | {} |\n",
+                l.offset, l.name, l.access, l.reset, l.brief
+            )
+            .as_str(),
+        );
+        result.push_str("| ------ | ---- | ------ | ----- | ----- |\n");
+        for register in &sorted {
+            result.push_str(
+                format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    table_cell(register.offset.as_deref().unwrap_or_default()),
+                    table_cell(&register.name),
+                    table_cell(register.access.as_deref().unwrap_or_default()),
+                    table_cell(register.reset.as_deref().unwrap_or_default()),
+                    table_cell(&register.desc)
+                )
+                .as_str(),
+            );
+        }
+        result.push('\n');
+        for register in &sorted {
+            let anchor = register_anchor(module, &register.name);
+            if symbols.is_anchored(&anchor) {
+                result.push_str(format!("<a id=\"{}\"></a>\n\n", anchor).as_str());
+            }
+            result.push_str(
+                format!(
+                    "{} {}. {}\n\n",
+                    heading(level),
+                    index.recall_and_step_forward(),
+                    register.name
+                )
+                .as_str(),
+            );
+            if !register.desc.is_empty() {
+                result.push_str(format!("{}\n\n", register.desc).as_str());
+            }
+            if register.fields.is_empty() {
+                continue;
+            }
+            let mut fields: Vec<_> = register.fields.iter().collect();
+            fields.sort_by_key(|x| std::cmp::Reverse(x.range().map(|x| x.0 as i64).unwrap_or(-1)));
+            result.push_str(
+                format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    l.bits, l.name, l.access, l.reset, l.brief
+                )
+                .as_str(),
+            );
+            result.push_str("| ---- | ---- | ------ | ----- | ----- |\n");
+            for field in fields {
+                result.push_str(
                     format!(
-                        "xxxxxxxxxxxxx[shape=record,label=\" {{ States | {} }} \"]\n",
-                        s
+                        "| {} | {} | {} | {} | {} |\n",
+                        table_cell(&field.bits),
+                        table_cell(&field.name),
+                        table_cell(field.access.as_deref().unwrap_or_default()),
+                        table_cell(field.reset.as_deref().unwrap_or_default()),
+                        table_cell(&field.desc)
                     )
                     .as_str(),
                 );
             }
+            result.push('\n');
+            result.push_str(
+                self.generate_bitfield(&register.name, register_bitfield(register))
+                    .as_str(),
+            );
+        }
+        result
+    }
 
-            gv.push_str("}");
-
-            fs::write(&temp_file, gv).unwrap();
+    fn format_params(&self, params: &[SvParam], symbols: &Symbols, module: Option<&str>) -> String {
+        let l = self.labels;
+        let mut result = String::new();
+        result.push_str(
+            format!(
+                "| {} | {} | {} | {} | {} |\n",
+                l.name, l.default, l.type_, l.dimensions, l.brief
+            )
+            .as_str(),
+        );
+        result.push_str("| ---- | ------- | ---- | ---------- | ----- |\n");
+        for param in params {
+            let name = self.anchored(
+                self.format_name(&param.name, &param.lifecycle),
+                module.map(|x| param_anchor(x, &param.name)),
+                symbols,
+            );
+            let brief = self.format_brief(&param.comment, &param.lifecycle);
+            let v = [
+                name.as_str(),
+                param.default.as_deref().unwrap_or(""),
+                param.param_type.as_deref().unwrap_or(""),
+                param.dimensions.as_deref().unwrap_or(""),
+                brief.as_str(),
+            ];
+            let v: Vec<String> = v.iter().map(|x| table_cell(x)).collect();
+            result.push_str(format!("| {} |\n", v.join(" | ")).as_str());
+        }
+        result.push('\n');
+        result
+    }
 
-            Command::new(graphviz)
-                .arg("-Tpng")
-                .arg(temp_file.to_str().unwrap())
-                .arg("-o")
-                .arg(file_path.to_str().unwrap())
-                .output()
-                .unwrap();
+    fn format_custom_tag(&self, tag: &str, name: Option<&str>, desc: &str) -> String {
+        let custom = self.config.tag(tag);
+        let label = custom.map(|x| x.label()).unwrap_or_else(|| tag.to_string());
+        let style = custom.map(|x| x.style).unwrap_or_default();
+        let text = match name {
+            Some(name) => format!("`{}` {}", name, desc),
+            None => desc.to_string(),
+        };
+        match style {
+            // column tags with a name are shown in the port table
+            TagStyle::Column if name.is_some() => String::new(),
+            TagStyle::Admonition if text.contains('\n') => {
+                format!("> **{}:**\n>\n{}\n", label, blockquote(&text))
+            }
+            TagStyle::Admonition => format!("> **{}:** {}\n\n", label, text),
+            _ => format!("**{}:** {}\n\n", label, text),
+        }
+    }
 
-            fs::remove_file(temp_file).unwrap();
-            format!("![fsm]({})", file_name)
-        } else {
-            let mut result = String::from("");
-            for (from, to, desc) in &fsm.transits {
-                result.push_str(format!("* {}->{}: {}\n", from, to, desc).as_str());
+    /// Domain cell of a port: its clock, or whether it is a clock or a reset.
+    fn format_clocking(&self, clocking: &Clocking) -> String {
+        let l = self.labels;
+        if clocking.clock {
+            return l.clock.to_string();
+        }
+        match &clocking.reset {
+            Some(reset) => {
+                let active = reset
+                    .active_low
+                    .map(|x| if x { l.active_low } else { l.active_high });
+                let kind = reset
+                    .asynchronous
+                    .map(|x| if x { l.asynchronous } else { l.synchronous });
+                let attrs: Vec<&str> = active.into_iter().chain(kind).collect();
+                if attrs.is_empty() {
+                    l.reset_signal.to_string()
+                } else {
+                    format!("{} ({})", l.reset_signal, attrs.join(", "))
+                }
             }
-            result.push_str("\n");
-            result
+            None => clocking.domain.clone().unwrap_or_default(),
         }
     }
 
-    fn generate_waveform(&self, s: &String) -> String {
-        if let Some(wavedrom) = &self.wavedrom {
-            let mut hasher = DefaultHasher::new();
-            s.hash(&mut hasher);
-            let hash = hasher.finish();
-            let file_name = format!("docgen_wave_{}.png", hash);
-            let file_path = Path::new(&self.cwd).join(&file_name);
-            let temp_file = Path::new(&self.cwd).join(".temp.json");
-            fs::write(&temp_file, s).unwrap();
-
-            Command::new(wavedrom)
-                .arg("-i")
-                .arg(temp_file.to_str().unwrap())
-                .arg("-p")
-                .arg(file_path.to_str().unwrap())
-                .output()
-                .unwrap();
-
-            fs::remove_file(temp_file).unwrap();
-
-            format!("![wave]({})", file_name)
-        } else {
-            s.clone()
+    /// Clocks of `module` with the resets and the ports synchronous to each of them.
+    fn format_clock_domains(&self, module: &SvModule) -> String {
+        let l = self.labels;
+        let mut clocks: Vec<&str> = Vec::new();
+        let names = module
+            .ports
+            .iter()
+            .filter(|x| x.clocking.clock)
+            .map(|x| x.name.as_str())
+            .chain(
+                module
+                    .ports
+                    .iter()
+                    .filter_map(|x| x.clocking.domain.as_deref()),
+            );
+        for name in names {
+            if !clocks.contains(&name) {
+                clocks.push(name);
+            }
         }
+        let mut result = format!(
+            "| {} | {} | {} |\n| ----- | ------ | ----- |\n",
+            l.clock, l.resets, l.synchronous_ports
+        );
+        let mut row = |clock: Option<&str>| {
+            let in_domain =
+                |x: &&SvPort| x.clocking.domain.as_deref() == clock && !x.clocking.clock;
+            let resets: Vec<String> = module
+                .ports
+                .iter()
+                .filter(in_domain)
+                .filter(|x| x.clocking.reset.is_some())
+                .map(|x| {
+                    let reset = self.format_clocking(&x.clocking);
+                    match reset.strip_prefix(l.reset_signal) {
+                        Some(attrs) => format!("{}{}", x.name, attrs),
+                        None => x.name.clone(),
+                    }
+                })
+                .collect();
+            let ports: Vec<&str> = module
+                .ports
+                .iter()
+                .filter(in_domain)
+                .filter(|x| clock.is_some() && x.clocking.reset.is_none())
+                .map(|x| x.name.as_str())
+                .collect();
+            if clock.is_some() || !resets.is_empty() {
+                let v = [clock.unwrap_or(""), &resets.join(", "), &ports.join(", ")];
+                let v: Vec<String> = v.iter().map(|x| table_cell(x)).collect();
+                result.push_str(format!("| {} |\n", v.join(" | ")).as_str());
+            }
+        };
+        for clock in &clocks {
+            row(Some(clock));
+        }
+        // resets not belonging to a clock
+        row(None);
+        result.push('\n');
+        result
     }
-}
 
-impl DocgenGenerator for MarkdownGenerator {
-    fn generate(&self, items: Vec<SvFile>) -> String {
-        let mut result = String::new();
-        let mut index = NumberedList::new();
+    /// Protocol and role of a port group, e.g. `AXI4-Lite slave`.
+    fn format_bus(&self, group: &SvPortGroup) -> String {
+        let role = group.role.map(|x| match x {
+            BusRole::Master => self.labels.master,
+            BusRole::Slave => self.labels.slave,
+        });
+        let words: Vec<&str> = group.protocol.as_deref().into_iter().chain(role).collect();
+        words.join(" ")
+    }
 
-        for file in items {
+    /// Sections of the port groups of `module` with their ports, under headings of `level`.
+    fn format_port_groups(
+        &self,
+        module: &SvModule,
+        index: &mut NumberedList,
+        level: usize,
+        symbols: &Symbols,
+    ) -> String {
+        let mut result = String::new();
+        for group in &module.port_groups {
             result.push_str(
                 format!(
-                    "# {}. File {}\n\n",
-                    index.recall_and_go_downstairs(),
-                    Path::new(file.name.as_str())
-                        .file_name()
-                        .and_then(|x| x.to_str())
-                        .unwrap_or("")
+                    "<a id=\"{}\"></a>\n\n",
+                    port_group_anchor(&module.name, &group.name)
                 )
                 .as_str(),
             );
-
-            if file.modules.len() > 0 {
-                for module in file.modules.iter() {
-                    result.push_str(
-                        format!(
-                            "## {}. module {}\n\n",
-                            index.recall_and_go_downstairs(),
-                            module.name
-                        )
-                        .as_str(),
-                    );
-                    if let Some(brief) = &module.brief {
-                        if brief != "" {
-                            result.push_str(format!("{}\n\n", brief).as_str());
-                        }
-                    }
-                    let s = self.format_comment(&module.comment);
-                    result.push_str(s.as_str());
-                    if module.params.len() > 0 {
-                        result.push_str(
-                            format!("### {}. Parameters\n\n", index.recall_and_step_forward())
-                                .as_str(),
-                        );
-                        result.push_str("| name | default | type | dimensions | brief |\n");
-                        result.push_str("| ---- | ------- | ---- | ---------- | ----- |\n");
-                        for param in &module.params {
-                            let v = vec![
-                                param.name.as_str(),
-                                param.default.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                param.param_type.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                param.dimensions.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                param.comment.as_str(),
-                            ];
-                            result.push_str(format!("| {} |\n", v.join(" | ")).as_str());
-                        }
-                        result.push_str("\n");
-                    }
-                    if module.ports.len() > 0 {
-                        result.push_str(
-                            format!("### {}. Ports\n\n", index.recall_and_step_forward()).as_str(),
-                        );
-                        result.push_str("| name | direction | type | dimensions | brief |\n");
-                        result.push_str("| ---- | --------- | ---- | ---------- | ----- |\n");
-                        for port in &module.ports {
-                            let v = vec![
-                                port.name.as_str(),
-                                port.direction.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                port.port_type.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                port.dimensions.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                port.comment.as_str(),
-                            ];
-                            result.push_str(format!("| {} |\n", v.join(" | ")).as_str());
-                        }
-                        result.push_str("\n");
-                    }
-                    for task in &module.tasks {
-                        result.push_str(
-                            format!(
-                                "### {}. {} {}\n\n",
-                                index.recall_and_go_downstairs(),
-                                if task.is_function { "function" } else { "task" },
-                                task.name
-                            )
-                            .as_str(),
-                        );
-                        if let Some(brief) = &task.brief {
-                            if brief != "" {
-                                result.push_str(format!("{}\n\n", brief).as_str());
-                            }
-                        }
-                        let s = self.format_comment(&task.comment);
-                        result.push_str(s.as_str());
-                        if task.params.len() > 0 {
-                            result.push_str(
-                                format!("### {}. Parameters\n\n", index.recall_and_step_forward())
-                                    .as_str(),
-                            );
-                            result.push_str("| name | default | type | dimensions | brief |\n");
-                            result.push_str("| ---- | ------- | ---- | ---------- | ----- |\n");
-                            for param in &task.params {
-                                let v = vec![
-                                    param.name.as_str(),
-                                    param.default.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                    param.param_type.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                    param.dimensions.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                    param.comment.as_str(),
-                                ];
-                                result.push_str(format!("| {} |\n", v.join(" | ")).as_str());
-                            }
-                            result.push_str("\n");
-                        }
-                        if task.ports.len() > 0 {
-                            result.push_str(
-                                format!("### {}. Ports\n\n", index.recall_and_step_forward())
-                                    .as_str(),
-                            );
-                            result.push_str("| name | direction | type | dimensions | brief |\n");
-                            result.push_str("| ---- | --------- | ---- | ---------- | ----- |\n");
-                            for port in &task.ports {
-                                let v = vec![
-                                    port.name.as_str(),
-                                    port.direction.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                    port.port_type.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                    port.dimensions.as_ref().map(|x| x.as_str()).unwrap_or(""),
-                                    port.comment.as_str(),
-                                ];
-                                result.push_str(format!("| {} |\n", v.join(" | ")).as_str());
-                            }
-                            result.push_str("\n");
-                        }
-                        index.go_upstairs();
-                    }
-                    index.go_upstairs();
-                }
+            result.push_str(
+                format!(
+                    "{} {}. {}\n\n",
+                    heading(level),
+                    index.recall_and_step_forward(),
+                    group.name
+                )
+                .as_str(),
+            );
+            let bus = self.format_bus(group);
+            if !bus.is_empty() {
+                result.push_str(format!("*{}*\n\n", bus).as_str());
             }
-        }
-        result
+            if !group.desc.is_empty() {
+                result.push_str(format!("{}\n\n", group.desc).as_str());
+            }
+            let ports: Vec<SvPort> = module
+                .ports
+                .iter()
+                .filter(|x| group.ports.contains(&x.name))
+                .cloned()
+                .collect();
+            result.push_str(
+                self.format_ports(&ports, &[], symbols, Some(&module.name))
+                    .as_str(),
+            );
+        }
+        result
+    }
+
+    /// Table of `ports`, where the ports of each of `groups` collapse into one row linking
+    /// to the section of the group.
+    fn format_ports(
+        &self,
+        ports: &[SvPort],
+        groups: &[SvPortGroup],
+        symbols: &Symbols,
+        module: Option<&str>,
+    ) -> String {
+        let l = self.labels;
+        let columns: Vec<&CustomTag> = self
+            .config
+            .tags
+            .iter()
+            .filter(|x| x.style == TagStyle::Column)
+            .filter(|x| {
+                ports
+                    .iter()
+                    .any(|p| p.tags.iter().any(|(t, _)| t == &x.name))
+            })
+            .collect();
+        let clocked = ports.iter().any(|x| !x.clocking.is_empty());
+        let mut result = String::new();
+        result.push_str(
+            format!(
+                "| {} | {} | {} | {} | {} |",
+                l.name, l.direction, l.type_, l.dimensions, l.brief
+            )
+            .as_str(),
+        );
+        if clocked {
+            result.push_str(format!(" {} |", l.domain).as_str());
+        }
+        for column in &columns {
+            result.push_str(format!(" {} |", column.label()).as_str());
+        }
+        result.push_str("\n| ---- | --------- | ---- | ---------- | ----- |");
+        if clocked {
+            result.push_str(" ------ |");
+        }
+        for _ in &columns {
+            result.push_str(" ----- |");
+        }
+        result.push('\n');
+        for port in ports {
+            if let Some(group) = groups.iter().find(|x| x.ports.contains(&port.name)) {
+                if group.ports.first() != Some(&port.name) {
+                    continue;
+                }
+                let anchor = port_group_anchor(module.unwrap_or_default(), &group.name);
+                let name = format!("[{}](#{})", group.name, anchor);
+                let bus = self.format_bus(group);
+                let mut v = vec![name.as_str(), "", bus.as_str(), "", group.desc.as_str()];
+                if clocked {
+                    v.push("");
+                }
+                v.extend(columns.iter().map(|_| ""));
+                let v: Vec<String> = v.iter().map(|x| table_cell(x)).collect();
+                result.push_str(format!("| {} |\n", v.join(" | ")).as_str());
+                continue;
+            }
+            let name = self.anchored(
+                self.format_name(&port.name, &port.lifecycle),
+                module.map(|x| port_anchor(x, &port.name)),
+                symbols,
+            );
+            let mut brief = self.format_brief(&port.comment, &port.lifecycle);
+            if let Some(source) = &port.inherited {
+                brief.push_str(format!(" *({} {{@link {}}})*", l.inherited, source).as_str());
+            }
+            let mut v = vec![
+                name.as_str(),
+                port.direction.as_deref().unwrap_or(""),
+                port.port_type.as_deref().unwrap_or(""),
+                port.dimensions.as_deref().unwrap_or(""),
+                brief.as_str(),
+            ];
+            let clocking = self.format_clocking(&port.clocking);
+            if clocked {
+                v.push(clocking.as_str());
+            }
+            for column in &columns {
+                let text = port
+                    .tags
+                    .iter()
+                    .find(|(t, _)| t == &column.name)
+                    .map(|(_, desc)| desc.as_str());
+                v.push(text.unwrap_or(""));
+            }
+            let v: Vec<String> = v.iter().map(|x| table_cell(x)).collect();
+            result.push_str(format!("| {} |\n", v.join(" | ")).as_str());
+        }
+        result.push('\n');
+        result
+    }
+
+    fn format_include_graph(&self, includes: &[(String, String)]) -> String {
+        let mut result = String::new();
+        let mut files: Vec<&String> = Vec::new();
+        for (file, _) in includes {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+        result.push_str(
+            format!(
+                "| {} | {} |\n",
+                self.labels.file.to_lowercase(),
+                self.labels.includes
+            )
+            .as_str(),
+        );
+        result.push_str("| ---- | -------- |\n");
+        for file in files {
+            let mut included: Vec<&str> = includes
+                .iter()
+                .filter(|(x, _)| x == file)
+                .map(|(_, y)| y.as_str())
+                .collect();
+            included.dedup();
+            result.push_str(format!("| {} | {} |\n", file, included.join(", ")).as_str());
+        }
+        result.push('\n');
+        result
+    }
+
+    /// Documentation of a module with its params, ports and functions/tasks, under a heading of `level`.
+    fn format_module(
+        &self,
+        module: &SvModule,
+        index: &mut NumberedList,
+        level: usize,
+        symbols: &Symbols,
+    ) -> String {
+        let mut result = String::new();
+        let sub = heading(level + 1);
+        if symbols.is_anchored(&module_anchor(module)) {
+            result.push_str(format!("<a id=\"{}\"></a>\n\n", module_anchor(module)).as_str());
+        }
+        result.push_str(
+            format!(
+                "{} {}. {} {}\n\n",
+                heading(level),
+                index.recall_and_go_downstairs(),
+                self.labels.module_kind(module.kind),
+                self.format_name(&module.name, &module.lifecycle)
+            )
+            .as_str(),
+        );
+        if let Some(brief) = &module.brief {
+            if !brief.is_empty() {
+                result.push_str(format!("{}\n\n", brief).as_str());
+            }
+        }
+        let s = self.format_comment(&module.comment);
+        result.push_str(s.as_str());
+        if !module.params.is_empty() {
+            result.push_str(
+                format!(
+                    "{} {}. {}\n\n",
+                    sub,
+                    index.recall_and_step_forward(),
+                    self.labels.parameters
+                )
+                .as_str(),
+            );
+            result.push_str(
+                self.format_params(&module.params, symbols, Some(&module.name))
+                    .as_str(),
+            );
+        }
+        if !module.typedefs.is_empty() {
+            result.push_str(
+                format!(
+                    "{} {}. {}\n\n",
+                    sub,
+                    index.recall_and_step_forward(),
+                    self.labels.types
+                )
+                .as_str(),
+            );
+            result.push_str(
+                self.format_typedefs(&module.typedefs, symbols, &module.name)
+                    .as_str(),
+            );
+        }
+        if !module.ports.is_empty() {
+            let grouped = !module.port_groups.is_empty();
+            let number = if grouped {
+                index.recall_and_go_downstairs()
+            } else {
+                index.recall_and_step_forward()
+            };
+            result.push_str(format!("{} {}. {}\n\n", sub, number, self.labels.ports).as_str());
+            result.push_str(
+                self.format_ports(
+                    &module.ports,
+                    &module.port_groups,
+                    symbols,
+                    Some(&module.name),
+                )
+                .as_str(),
+            );
+            if grouped {
+                result.push_str(
+                    self.format_port_groups(module, index, level + 2, symbols)
+                        .as_str(),
+                );
+                index.go_upstairs();
+                index.step_forward();
+            }
+        }
+        if module.ports.iter().any(|x| !x.clocking.is_empty()) {
+            result.push_str(
+                format!(
+                    "{} {}. {}\n\n",
+                    sub,
+                    index.recall_and_step_forward(),
+                    self.labels.clock_domains
+                )
+                .as_str(),
+            );
+            result.push_str(self.format_clock_domains(module).as_str());
+        }
+        if !module.registers.is_empty() {
+            result.push_str(
+                format!(
+                    "{} {}. {}\n\n",
+                    sub,
+                    index.recall_and_go_downstairs(),
+                    self.labels.registers
+                )
+                .as_str(),
+            );
+            result.push_str(
+                self.format_registers(&module.registers, index, level + 2, symbols, &module.name)
+                    .as_str(),
+            );
+            index.go_upstairs();
+            index.step_forward();
+        }
+        for task in &module.tasks {
+            if symbols.is_anchored(&task_anchor(module, task)) {
+                result
+                    .push_str(format!("<a id=\"{}\"></a>\n\n", task_anchor(module, task)).as_str());
+            }
+            result.push_str(
+                format!(
+                    "{} {}. {} {}\n\n",
+                    sub,
+                    index.recall_and_go_downstairs(),
+                    if task.is_function {
+                        self.labels.function
+                    } else {
+                        self.labels.task
+                    },
+                    self.format_name(&task.name, &task.lifecycle)
+                )
+                .as_str(),
+            );
+            if let Some(brief) = &task.brief {
+                if !brief.is_empty() {
+                    result.push_str(format!("{}\n\n", brief).as_str());
+                }
+            }
+            let s = self.format_comment(&task.comment);
+            result.push_str(s.as_str());
+            if !task.params.is_empty() {
+                result.push_str(
+                    format!(
+                        "{} {}. {}\n\n",
+                        sub,
+                        index.recall_and_step_forward(),
+                        self.labels.parameters
+                    )
+                    .as_str(),
+                );
+                result.push_str(self.format_params(&task.params, symbols, None).as_str());
+            }
+            if !task.ports.is_empty() {
+                result.push_str(
+                    format!(
+                        "{} {}. {}\n\n",
+                        sub,
+                        index.recall_and_step_forward(),
+                        self.labels.ports
+                    )
+                    .as_str(),
+                );
+                result.push_str(self.format_ports(&task.ports, &[], symbols, None).as_str());
+            }
+            index.go_upstairs();
+            index.step_forward();
+        }
+        index.go_upstairs();
+        index.step_forward();
+        self.resolve_links(&result, symbols, Some(&module.name))
     }
+
+    /// Document structure following the group tree, with the ungrouped modules at the end.
+    fn format_groups(
+        &self,
+        items: &[SvFile],
+        index: &mut NumberedList,
+        symbols: &Symbols,
+    ) -> String {
+        let tree = GroupTree::new(items);
+        let ungrouped: Vec<&SvModule> = tree
+            .modules
+            .iter()
+            .filter(|x| x.groups.is_empty())
+            .cloned()
+            .collect();
+
+        let mut result = String::new();
+        for root in &tree.roots {
+            result.push_str(self.format_group_nav(*root, &tree, 0).as_str());
+        }
+        if !ungrouped.is_empty() {
+            result.push_str(format!("* [{}](#ungrouped)\n", self.labels.ungrouped).as_str());
+            for module in &ungrouped {
+                result.push_str(
+                    format!(
+                        "  * [{} {}](#{}){}\n",
+                        self.labels.module_kind(module.kind),
+                        module.name,
+                        module_anchor(module),
+                        nav_brief(module)
+                    )
+                    .as_str(),
+                );
+            }
+        }
+        result.push('\n');
+
+        for root in &tree.roots {
+            result.push_str(self.format_group(*root, &tree, index, 1, symbols).as_str());
+        }
+        if !ungrouped.is_empty() {
+            result.push_str("<a id=\"ungrouped\"></a>\n\n");
+            result.push_str(
+                format!(
+                    "# {}. {}\n\n",
+                    index.recall_and_go_downstairs(),
+                    self.labels.ungrouped
+                )
+                .as_str(),
+            );
+            for module in &ungrouped {
+                result.push_str(self.format_module(module, index, 2, symbols).as_str());
+            }
+            index.go_upstairs();
+            index.step_forward();
+        }
+        result
+    }
+
+    fn format_group_nav(&self, group: usize, tree: &GroupTree, depth: usize) -> String {
+        let GroupTree {
+            groups,
+            children,
+            modules,
+            ..
+        } = tree;
+        let indent = "  ".repeat(depth);
+        let name = &groups[group].name;
+        let mut result = format!("{}* [{}](#group-{})\n", indent, groups[group].title, name);
+        for module in modules.iter().filter(|x| x.groups.first() == Some(name)) {
+            result.push_str(
+                format!(
+                    "{}  * [{} {}](#{}){}\n",
+                    indent,
+                    self.labels.module_kind(module.kind),
+                    module.name,
+                    module_anchor(module),
+                    nav_brief(module)
+                )
+                .as_str(),
+            );
+        }
+        for module in modules {
+            for task in module.tasks.iter().filter(|x| x.groups.contains(name)) {
+                result.push_str(
+                    format!(
+                        "{}  * [{} {}.{}](#{})\n",
+                        indent,
+                        if task.is_function {
+                            self.labels.function
+                        } else {
+                            self.labels.task
+                        },
+                        module.name,
+                        task.name,
+                        task_anchor(module, task)
+                    )
+                    .as_str(),
+                );
+            }
+        }
+        for child in &children[group] {
+            result.push_str(self.format_group_nav(*child, tree, depth + 1).as_str());
+        }
+        result
+    }
+
+    fn format_group(
+        &self,
+        group: usize,
+        tree: &GroupTree,
+        index: &mut NumberedList,
+        level: usize,
+        symbols: &Symbols,
+    ) -> String {
+        let SvGroup {
+            name,
+            title,
+            brief,
+            comment,
+            ..
+        } = &tree.groups[group];
+        let GroupTree {
+            children, modules, ..
+        } = tree;
+        let mut result = String::new();
+        result.push_str(format!("<a id=\"group-{}\"></a>\n\n", name).as_str());
+        result.push_str(
+            format!(
+                "{} {}. {}\n\n",
+                heading(level),
+                index.recall_and_go_downstairs(),
+                title
+            )
+            .as_str(),
+        );
+        if let Some(brief) = brief {
+            result.push_str(format!("{}\n\n", brief).as_str());
+        }
+        result.push_str(self.format_comment(comment).as_str());
+
+        // members documented in another place
+        let mut links = String::new();
+        for module in modules {
+            if module.groups.contains(name) && module.groups.first() != Some(name) {
+                links.push_str(
+                    format!(
+                        "* {} [{}](#{})\n",
+                        self.labels.module_kind(module.kind),
+                        module.name,
+                        module_anchor(module)
+                    )
+                    .as_str(),
+                );
+            }
+            for task in module.tasks.iter().filter(|x| x.groups.contains(name)) {
+                links.push_str(
+                    format!(
+                        "* {} [{}.{}](#{})\n",
+                        if task.is_function {
+                            self.labels.function
+                        } else {
+                            self.labels.task
+                        },
+                        module.name,
+                        task.name,
+                        task_anchor(module, task)
+                    )
+                    .as_str(),
+                );
+            }
+        }
+        if !links.is_empty() {
+            result.push_str(format!("{}\n", links).as_str());
+        }
+        let mut result = self.resolve_links(&result, symbols, None);
+
+        for module in modules.iter().filter(|x| x.groups.first() == Some(name)) {
+            result.push_str(
+                self.format_module(module, index, level + 1, symbols)
+                    .as_str(),
+            );
+        }
+        for child in &children[group] {
+            result.push_str(
+                self.format_group(*child, tree, index, level + 1, symbols)
+                    .as_str(),
+            );
+        }
+        index.go_upstairs();
+        index.step_forward();
+        result
+    }
+
+    fn format_deprecated_items(&self, items: &[(String, String, String)]) -> String {
+        let l = self.labels;
+        let mut result = String::new();
+        result.push_str(format!("| {} | {} | {} |\n", l.name, l.type_, l.reason).as_str());
+        result.push_str("| ---- | ---- | ------ |\n");
+        for (kind, item, reason) in items {
+            result
+                .push_str(format!("| `{}` | {} | {} |\n", item, kind, table_cell(reason)).as_str());
+        }
+        result.push('\n');
+        result
+    }
+
+    fn format_version_history(
+        &self,
+        index: &mut NumberedList,
+        items: &[(String, String, String)],
+    ) -> String {
+        let mut items: Vec<&(String, String, String)> = items.iter().collect();
+        items.sort_by(|a, b| compare_versions(&a.2, &b.2));
+        let mut result = String::new();
+        let mut current: Option<&str> = None;
+        for (kind, item, version) in items {
+            if current != Some(version.as_str()) {
+                if current.is_some() {
+                    result.push('\n');
+                }
+                current = Some(version.as_str());
+                result.push_str(
+                    format!(
+                        "## {}. {} {}\n\n",
+                        index.recall_and_step_forward(),
+                        self.labels.added_in,
+                        version
+                    )
+                    .as_str(),
+                );
+            }
+            result.push_str(format!("* {} `{}`\n", kind, item).as_str());
+        }
+        result.push('\n');
+        result
+    }
+
+    fn format_open_items(&self, items: &[SvOpenItem]) -> String {
+        let l = self.labels;
+        let mut result = String::new();
+        result.push_str(
+            format!(
+                "| {} | {} | {} | {} |\n",
+                l.name, l.type_, l.location, l.brief
+            )
+            .as_str(),
+        );
+        result.push_str("| ---- | ---- | -------- | ----- |\n");
+        for item in items {
+            result.push_str(
+                format!(
+                    "| `{}` | {} | {}:{} | {} |\n",
+                    item.entity,
+                    l.open_kind(item.kind),
+                    item.file,
+                    item.line,
+                    table_cell(&item.text)
+                )
+                .as_str(),
+            );
+        }
+        result.push('\n');
+        result
+    }
+
+    fn trace_kind(&self, kind: TraceKind) -> &'static str {
+        match kind {
+            TraceKind::Module(kind) => self.labels.module_kind(kind),
+            TraceKind::Function => self.labels.function,
+            TraceKind::Task => self.labels.task,
+            TraceKind::Port => self.labels.port,
+            TraceKind::Transition => self.labels.transition,
+        }
+    }
+
+    /// Tables from each requirement to the design elements covering it, and back.
+    fn format_traceability(&self, index: &mut NumberedList, traces: &[SvTrace]) -> String {
+        let l = self.labels;
+        let mut result = String::new();
+        let mut requirements: Vec<(&str, &str)> = self
+            .requirements
+            .iter()
+            .map(|(id, title)| (id.as_str(), title.as_str()))
+            .collect();
+        for trace in traces {
+            if !requirements.iter().any(|x| x.0 == trace.requirement) {
+                requirements.push((trace.requirement.as_str(), ""));
+            }
+        }
+        result.push_str(
+            format!(
+                "## {}. {}\n\n",
+                index.recall_and_step_forward(),
+                l.requirements_to_entities
+            )
+            .as_str(),
+        );
+        result.push_str(format!("| {} | {} | {} |\n", l.requirement, l.brief, l.entities).as_str());
+        result.push_str("| ----------- | ----- | -------- |\n");
+        for (id, title) in &requirements {
+            let entities: Vec<String> = traces
+                .iter()
+                .filter(|x| x.requirement == *id)
+                .map(|x| format!("`{}`", x.entity))
+                .collect();
+            let entities = if entities.is_empty() {
+                format!("*{}*", l.not_covered)
+            } else {
+                entities.join(", ")
+            };
+            result
+                .push_str(format!("| {} | {} | {} |\n", id, table_cell(title), entities).as_str());
+        }
+        result.push('\n');
+
+        result.push_str(
+            format!(
+                "## {}. {}\n\n",
+                index.recall_and_step_forward(),
+                l.entities_to_requirements
+            )
+            .as_str(),
+        );
+        result.push_str(format!("| {} | {} | {} |\n", l.name, l.type_, l.requirements).as_str());
+        result.push_str("| ---- | ---- | ------------ |\n");
+        let mut entities: Vec<(&str, TraceKind)> = Vec::new();
+        for trace in traces {
+            if !entities.iter().any(|x| x.0 == trace.entity) {
+                entities.push((trace.entity.as_str(), trace.kind));
+            }
+        }
+        for (entity, kind) in entities {
+            let ids: Vec<&str> = traces
+                .iter()
+                .filter(|x| x.entity == entity)
+                .map(|x| x.requirement.as_str())
+                .collect();
+            result.push_str(
+                format!(
+                    "| `{}` | {} | {} |\n",
+                    entity,
+                    self.trace_kind(kind),
+                    ids.join(", ")
+                )
+                .as_str(),
+            );
+        }
+        result.push('\n');
+        result
+    }
+
+    fn generate_fsm(&self, fsm: &Fsm) -> String {
+        if let Some(graphviz) = &self.graphviz {
+            let mut gv = String::from("digraph G {\n");
+
+            for (from, to, desc) in &fsm.transits {
+                gv.push_str(format!("{}->{}[label=\"{}\"]\n", from, to, desc).as_str());
+            }
+
+            if !fsm.states.is_empty() {
+                let s = fsm
+                    .states
+                    .iter()
+                    .map(|(state, desc)| format!("{{ {} | {} }}", state, desc))
+                    .collect::<Vec<String>>()
+                    .join("|");
+                gv.push_str(
+                    format!(
+                        "xxxxxxxxxxxxx[shape=record,label=\" {{ States | {} }} \"]\n",
+                        s
+                    )
+                    .as_str(),
+                );
+            }
+
+            gv.push('}');
+
+            self.render_image("fsm", hash_of(fsm), ".temp.gv", &gv, |input, output| {
+                let mut command = Command::new(graphviz);
+                command.arg("-Tpng").arg(input).arg("-o").arg(output);
+                command
+            })
+        } else {
+            let mut result = String::from("");
+            for (from, to, desc) in &fsm.transits {
+                result.push_str(format!("* {}->{}: {}\n", from, to, desc).as_str());
+            }
+            result.push('\n');
+            result
+        }
+    }
+
+    /// Bitfield diagram derived from a layout, kept as a `wavedrom` code block when wavedrom
+    /// is not given.
+    fn generate_bitfield(&self, name: &str, json: Option<String>) -> String {
+        match (&self.wavedrom, json) {
+            (Some(_), Some(json)) => format!(
+                "**{}:** {}\n\n {}\n\n",
+                self.labels.bitfield,
+                name,
+                self.generate_waveform(&json)
+            ),
+            (None, Some(json)) => format!(
+                "**{}:** {}\n\n{}\n",
+                self.labels.bitfield,
+                name,
+                fenced_code(Some("wavedrom"), &json)
+            ),
+            (_, None) => String::new(),
+        }
+    }
+
+    fn generate_waveform(&self, s: &String) -> String {
+        if let Some(wavedrom) = &self.wavedrom {
+            self.render_image("wave", hash_of(s), ".temp.json", s, |input, output| {
+                let mut command = Command::new(wavedrom);
+                command.arg("-i").arg(input).arg("-p").arg(output);
+                command
+            })
+        } else {
+            s.clone()
+        }
+    }
+
+    /// Image of a `@dot` or `@plantuml` diagram, or the source as a code block when its
+    /// renderer is not given. Mermaid is left to the Markdown viewer.
+    fn generate_diagram(&self, kind: DiagramKind, source: &str) -> String {
+        match (kind, &self.graphviz, &self.plantuml) {
+            (DiagramKind::Dot, Some(graphviz), _) => self.render_image(
+                "dot",
+                hash_of(source),
+                ".temp.gv",
+                source,
+                |input, output| {
+                    let mut command = Command::new(graphviz);
+                    command.arg("-Tpng").arg(input).arg("-o").arg(output);
+                    command
+                },
+            ),
+            (DiagramKind::PlantUml, _, Some(plantuml)) => {
+                let source = if source.trim_start().starts_with("@start") {
+                    source.to_string()
+                } else {
+                    format!("@startuml\n{}\n@enduml\n", source)
+                };
+                self.render_image(
+                    "uml",
+                    hash_of(&source),
+                    ".temp.puml",
+                    &source,
+                    |input, output| {
+                        let mut command = Command::new(plantuml);
+                        command
+                            .arg("-tpng")
+                            .arg("-pipe")
+                            .stdin(fs::File::open(input).unwrap())
+                            .stdout(fs::File::create(output).unwrap());
+                        command
+                    },
+                )
+            }
+            (DiagramKind::Dot, ..) => fenced_code(Some("dot"), source),
+            (DiagramKind::Mermaid, ..) => fenced_code(Some("mermaid"), source),
+            (DiagramKind::PlantUml, ..) => fenced_code(Some("plantuml"), source),
+        }
+    }
+
+    /// Copies an `@image` into the output directory as `docgen_img_<hash>.<ext>` and returns
+    /// its new link; URLs and missing images keep their link.
+    fn copy_image(&self, path: &str) -> String {
+        let source = Path::new(path);
+        if is_url(path) || !source.is_file() {
+            return path.to_string();
+        }
+        let file_name = match source.extension() {
+            Some(ext) => format!("docgen_img_{}.{}", hash_of(path), ext.to_string_lossy()),
+            None => format!("docgen_img_{}", hash_of(path)),
+        };
+        match fs::copy(source, Path::new(&self.cwd).join(&file_name)) {
+            Ok(_) => file_name,
+            Err(e) => {
+                eprintln!("error: unable to copy image {}: {}", path, e);
+                path.to_string()
+            }
+        }
+    }
+
+    /// Runs the renderer built by `command` from the input file holding `source` and the image
+    /// file, and links the image `docgen_<kind>_<hash>.png`.
+    fn render_image(
+        &self,
+        kind: &str,
+        hash: u64,
+        temp: &str,
+        source: &str,
+        command: impl FnOnce(&Path, &Path) -> Command,
+    ) -> String {
+        let file_name = format!("docgen_{}_{}.png", kind, hash);
+        let file_path = Path::new(&self.cwd).join(&file_name);
+        let temp_file = Path::new(&self.cwd).join(temp);
+        fs::write(&temp_file, source).unwrap();
+
+        command(&temp_file, &file_path).output().unwrap();
+
+        fs::remove_file(temp_file).unwrap();
+        format!("![{}]({})", kind, file_name)
+    }
+}
+
+impl DocgenGenerator for MarkdownGenerator {
+    fn generate(&self, items: Vec<SvFile>) -> String {
+        let mut result = String::new();
+        let mut index = NumberedList::new();
+        let mut includes: Vec<(String, String)> = Vec::new();
+        let versions = VersionIndex::new(self.labels, &items);
+        let mut open_items: Vec<(String, Vec<SvOpenItem>)> = Vec::new();
+        let traces: Vec<SvTrace> = items.iter().flat_map(|x| x.traces()).collect();
+
+        let grouped = items
+            .iter()
+            .any(|x| !x.groups.is_empty() || x.modules.iter().any(|y| !y.groups.is_empty()));
+        let mut symbols = Symbols::new(&items);
+        // the contents list links to every group and module
+        symbols.all_anchors = grouped;
+        if grouped {
+            result.push_str(self.format_groups(&items, &mut index, &symbols).as_str());
+        }
+        for file in &items {
+            if !grouped {
+                result.push_str(
+                    format!(
+                        "# {}. {} {}\n\n",
+                        index.recall_and_go_downstairs(),
+                        self.labels.file,
+                        Path::new(file.name.as_str())
+                            .file_name()
+                            .and_then(|x| x.to_str())
+                            .unwrap_or("")
+                    )
+                    .as_str(),
+                );
+                for module in file.modules.iter() {
+                    result.push_str(self.format_module(module, &mut index, 2, &symbols).as_str());
+                }
+                index.go_upstairs();
+                index.step_forward();
+            }
+            includes.extend(file.includes.iter().cloned());
+            let items = file.open_items();
+            if !items.is_empty() {
+                open_items.push((file.name.clone(), items));
+            }
+        }
+
+        if !versions.deprecated.is_empty() {
+            result.push_str(
+                format!(
+                    "# {}. {}\n\n",
+                    index.recall_and_step_forward(),
+                    self.labels.deprecated_items
+                )
+                .as_str(),
+            );
+            result.push_str(self.format_deprecated_items(&versions.deprecated).as_str());
+        }
+        if !versions.added.is_empty() {
+            result.push_str(
+                format!(
+                    "# {}. {}\n\n",
+                    index.recall_and_go_downstairs(),
+                    self.labels.version_history
+                )
+                .as_str(),
+            );
+            result.push_str(
+                self.format_version_history(&mut index, &versions.added)
+                    .as_str(),
+            );
+            index.go_upstairs();
+            index.step_forward();
+        }
+        if !open_items.is_empty() {
+            result.push_str(
+                format!(
+                    "# {}. {}\n\n",
+                    index.recall_and_go_downstairs(),
+                    self.labels.open_items
+                )
+                .as_str(),
+            );
+            for (file, file_items) in &open_items {
+                result.push_str(
+                    format!(
+                        "## {}. {} {}\n\n",
+                        index.recall_and_go_downstairs(),
+                        self.labels.file,
+                        Path::new(file.as_str())
+                            .file_name()
+                            .and_then(|x| x.to_str())
+                            .unwrap_or("")
+                    )
+                    .as_str(),
+                );
+                let mut modules: Vec<&str> = Vec::new();
+                for item in file_items {
+                    if !modules.contains(&item.module.as_str()) {
+                        modules.push(item.module.as_str());
+                    }
+                }
+                for module in modules {
+                    let kind = items
+                        .iter()
+                        .flat_map(|x| x.modules.iter())
+                        .find(|x| x.name == module)
+                        .map_or(self.labels.module, |x| self.labels.module_kind(x.kind));
+                    result.push_str(
+                        format!(
+                            "### {}. {} {}\n\n",
+                            index.recall_and_step_forward(),
+                            kind,
+                            module
+                        )
+                        .as_str(),
+                    );
+                    let module_items: Vec<SvOpenItem> = file_items
+                        .iter()
+                        .filter(|x| x.module == module)
+                        .cloned()
+                        .collect();
+                    result.push_str(self.format_open_items(&module_items).as_str());
+                }
+                index.go_upstairs();
+                index.step_forward();
+            }
+            index.go_upstairs();
+            index.step_forward();
+        }
+        if !traces.is_empty() || !self.requirements.is_empty() {
+            result.push_str(
+                format!(
+                    "# {}. {}\n\n",
+                    index.recall_and_go_downstairs(),
+                    self.labels.traceability
+                )
+                .as_str(),
+            );
+            result.push_str(self.format_traceability(&mut index, &traces).as_str());
+            index.go_upstairs();
+            index.step_forward();
+        }
+        if self.include_graph && !includes.is_empty() {
+            result.push_str(
+                format!(
+                    "# {}. {}\n\n",
+                    index.recall_and_step_forward(),
+                    self.labels.include_graph
+                )
+                .as_str(),
+            );
+            result.push_str(self.format_include_graph(&includes).as_str());
+        }
+        result
+    }
+}
+
+#[test]
+fn test_register_bitfield() {
+    use crate::docgen::SvField;
+
+    let field = |name: &str, bits: &str, access: Option<&str>| SvField {
+        name: name.to_string(),
+        bits: bits.to_string(),
+        access: access.map(String::from),
+        reset: None,
+        desc: String::new(),
+    };
+    let register = SvRegister {
+        name: "CTRL".to_string(),
+        offset: None,
+        access: None,
+        reset: None,
+        width: 16,
+        desc: String::new(),
+        fields: vec![field("mode", "7:4", Some("RW")), field("en", "1", None)],
+    };
+    // bits 0, 3:2 and 15:8 are reserved
+    assert_eq!(
+        register_bitfield(&register).unwrap(),
+        "{\"reg\": [{\"bits\": 1}, {\"bits\": 1, \"name\": \"en\"}, {\"bits\": 2}, \
+         {\"bits\": 4, \"name\": \"mode\", \"attr\": \"RW\"}, {\"bits\": 8}], \
+         \"config\": {\"bits\": 16}}"
+    );
+    let overlapping = SvRegister {
+        fields: vec![field("a", "3:0", None), field("b", "2", None)],
+        ..register
+    };
+    assert!(register_bitfield(&overlapping).is_none());
+}
+
+#[test]
+fn test_open_items_page() {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    let parse = |input: &str, name: &str| {
+        Docgen::new(input, name, &HashMap::new(), &Vec::<PathBuf>::new())
+            .unwrap()
+            .parse_tree()
+    };
+    let a = parse(
+        "/// @todo first\nmodule a (input x ///< @bug x | y\n);\nendmodule\n/// @fixme second\ninterface b;\nendinterface\n",
+        "rtl/a.sv",
+    );
+    let c = parse("/// @todo third\nmodule c;\nendmodule\n", "rtl/c.sv");
+    let generator = MarkdownGenerator::new(".".to_string(), None, None, None);
+    let doc = generator.generate(vec![a, c]);
+    let page = &doc[doc.find("# 3. Open items").unwrap()..];
+    assert_eq!(
+        page,
+        "# 3. Open items
+
+## 3.1. File a.sv
+
+### 3.1.1. module a
+
+| name | type | location | brief |
+| ---- | ---- | -------- | ----- |
+| `a` | TODO | rtl/a.sv:1 | first |
+| `a.x` | BUG | rtl/a.sv:2 | x \\| y |
+
+### 3.1.2. interface b
+
+| name | type | location | brief |
+| ---- | ---- | -------- | ----- |
+| `b` | FIXME | rtl/a.sv:5 | second |
+
+## 3.2. File c.sv
+
+### 3.2.1. module c
+
+| name | type | location | brief |
+| ---- | ---- | -------- | ----- |
+| `c` | TODO | rtl/c.sv:1 | third |
+
+"
+    );
+}
+
+#[test]
+fn test_traceability_page() {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    let input = "/// @req SYS-1, SYS-2
+/// @req IDLE -> BUSY: SYS-3
+module m (
+    input a, ///< @req SYS-2
+    input b
+);
+endmodule
+";
+    let file = Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let mut generator = MarkdownGenerator::new(".".to_string(), None, None, None);
+    generator.requirements = vec![
+        ("SYS-1".to_string(), "Reset | idle".to_string()),
+        ("SYS-2".to_string(), "Inputs".to_string()),
+        ("SYS-4".to_string(), "Stop".to_string()),
+    ];
+    let doc = generator.generate(vec![file]);
+    let page = &doc[doc.find("# 2. Traceability").unwrap()..];
+    assert_eq!(
+        page,
+        "# 2. Traceability
+
+## 2.1. Requirements
+
+| requirement | brief | design elements |
+| ----------- | ----- | -------- |
+| SYS-1 | Reset \\| idle | `m` |
+| SYS-2 | Inputs | `m`, `m.a` |
+| SYS-4 | Stop | *not covered* |
+| SYS-3 |  | `m: IDLE -> BUSY` |
+
+## 2.2. Design elements
+
+| name | type | requirements |
+| ---- | ---- | ------------ |
+| `m` | module | SYS-1, SYS-2 |
+| `m: IDLE -> BUSY` | transition | SYS-3 |
+| `m.a` | port | SYS-2 |
+
+"
+    );
+}
+
+#[test]
+fn test_table_cell() {
+    assert_eq!(table_cell("  a | b  "), "a \\| b");
+    assert_eq!(
+        table_cell("first line\nsecond | line\n"),
+        "first line<br>second \\| line"
+    );
+    assert_eq!(table_cell("`a||b`"), "`a\\|\\|b`");
+}
+
+#[test]
+fn test_group_cycle() {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    let input = "/**
+ * @defgroup a Group A
+ * @ingroup b
+ */
+/**
+ * @defgroup b Group B
+ * @ingroup a
+ */
+/**
+ * @defgroup c Group C
+ * @ingroup c
+ */
+/// @ingroup b
+module m;
+    /// @ingroup a
+    function f;
+    endfunction
+endmodule
+";
+    let file = Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let (roots, children) = group_tree(&file.groups);
+    // a group in itself is not nested, and the first group of the cycle becomes a root
+    assert_eq!(roots, vec![2, 0]);
+    assert_eq!(children, vec![vec![1], vec![], vec![]]);
+
+    let generator = MarkdownGenerator::new(".".to_string(), None, None, None);
+    let doc = generator.generate(vec![file]);
+    assert!(doc.starts_with(
+        "* [Group C](#group-c)
+* [Group A](#group-a)
+  * [function m.f](#function-m-f)
+  * [Group B](#group-b)
+    * [module m](#module-m)
+"
+    ));
+}
+
+#[test]
+fn test_deprecated_items() {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    let input = "/**
+ * @deprecated Note: use m2 | m3
+ * @deprecated a: use b
+ */
+module m (input a, input b);
+    /// @deprecated
+    task t;
+    endtask
+endmodule
+";
+    let file = Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let generator = MarkdownGenerator::new(".".to_string(), None, None, None);
+    let doc = generator.generate(vec![file]);
+    let index = &doc[doc.find("Deprecated items").unwrap()..];
+    assert!(index.starts_with(
+        "Deprecated items
+
+| name | type | reason |
+| ---- | ---- | ------ |
+| `m` | module | Note: use m2 \\| m3 |
+| `m.a` | port | use b |
+| `m.t` | task |  |
+
+"
+    ));
 }
@@ -0,0 +1,133 @@
+use crate::docgen::{SvFile, SvInstance, SvModule, SvPort};
+
+/// Documented port of `child` that `instance` connects directly to the signal `signal`, as in
+/// `.din(din)`.
+fn connected_port<'a>(
+    instance: &SvInstance,
+    child: &'a SvModule,
+    signal: &str,
+) -> Option<&'a SvPort> {
+    let ports = instance
+        .connections
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, expression))| expression == signal)
+        .filter_map(|(i, (port, _))| match port {
+            Some(port) => child.ports.iter().find(|x| &x.name == port),
+            None => child.ports.get(i),
+        });
+    // `.*` connects the ports not connected by name to the signals of the same name
+    let wildcard = child.ports.iter().filter(|x| {
+        instance.wildcard
+            && x.name == signal
+            && !instance
+                .connections
+                .iter()
+                .any(|(port, _)| port.as_deref() == Some(&x.name))
+    });
+    ports.chain(wildcard).find(|x| !x.comment.is_empty())
+}
+
+/// Fills in the missing port descriptions of modules from the child ports they are passed
+/// through to, repeating until ports passed through several levels are documented.
+pub fn inherit_port_comments(files: &mut [SvFile]) {
+    loop {
+        let modules: Vec<SvModule> = files
+            .iter()
+            .flat_map(|x| x.modules.iter().cloned())
+            .collect();
+        let mut changed = false;
+        for module in files.iter_mut().flat_map(|x| x.modules.iter_mut()) {
+            for port in module.ports.iter_mut().filter(|x| x.comment.is_empty()) {
+                let source = module.instances.iter().find_map(|instance| {
+                    let child = modules.iter().find(|x| x.name == instance.module)?;
+                    connected_port(instance, child, &port.name).map(|x| (child, x))
+                });
+                if let Some((child, source)) = source {
+                    port.comment = source.comment.clone();
+                    port.inherited = Some(format!("{}.{}", child.name, source.name));
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+#[test]
+fn test_inherit_port_comments() {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    let input = "module leaf (
+    input a, ///< leaf a
+    input b, ///< leaf b
+    input c, ///< leaf c
+    input d  ///< leaf d
+);
+endmodule
+
+module named (
+    input a,
+    input b,
+    input c,
+    input d ///< own d
+);
+    leaf u_leaf (.a(a), .b, .c(c & d), .d(d));
+endmodule
+
+module ordered (
+    input x,
+    input y
+);
+    leaf u_leaf (x, y, , );
+endmodule
+
+module top (
+    input a,
+    input b,
+    input c
+);
+    named u_named (.c(1'b0), .*);
+endmodule
+";
+    let mut files = vec![
+        Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+            .unwrap()
+            .parse_tree(),
+    ];
+    inherit_port_comments(&mut files);
+    let comments = |module: usize| -> Vec<(&str, Option<&str>)> {
+        files[0].modules[module]
+            .ports
+            .iter()
+            .map(|x| (x.comment.trim(), x.inherited.as_deref()))
+            .collect()
+    };
+    // `.a(a)` and `.b`; `c` is not passed through and `d` keeps its own description
+    assert_eq!(
+        comments(1),
+        vec![
+            ("leaf a", Some("leaf.a")),
+            ("leaf b", Some("leaf.b")),
+            ("", None),
+            ("own d", None),
+        ]
+    );
+    // by position
+    assert_eq!(
+        comments(2),
+        vec![("leaf a", Some("leaf.a")), ("leaf b", Some("leaf.b"))]
+    );
+    // `.*` over two levels, except for the port connected by name
+    assert_eq!(
+        comments(3),
+        vec![
+            ("leaf a", Some("named.a")),
+            ("leaf b", Some("named.b")),
+            ("", None),
+        ]
+    );
+}
@@ -0,0 +1,365 @@
+use crate::{
+    comment_parser::CommentItem,
+    docgen::{ModuleKind, SvFile, SvModule, SvParam, SvPort},
+};
+
+const IPXACT_NS: &str = "http://www.accellera.org/XMLSchema/IPXACT/1685-2014";
+const IPXACT_TYPES: [&str; 8] = [
+    "bit",
+    "byte",
+    "shortint",
+    "int",
+    "longint",
+    "shortreal",
+    "real",
+    "string",
+];
+
+pub struct IpxactGenerator {
+    pub vendor: String,
+    pub library: String,
+    pub version: String,
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Splits dimensions such as `[7:0][3:0]` into `(left, right)` pairs; `[N]` becomes `(N-1, 0)`.
+fn parse_ranges(dimensions: &str) -> Vec<(String, String)> {
+    dimensions
+        .split(']')
+        .filter_map(|x| x.trim().strip_prefix('['))
+        .map(|x| match x.split_once(':') {
+            Some((left, right)) => (left.trim().to_string(), right.trim().to_string()),
+            None => (format!("{}-1", x.trim()), "0".to_string()),
+        })
+        .collect()
+}
+
+/// Replaces the parameters of `module` referenced in `expr` by their `parameterId`s. Number
+/// literals such as `8'hff` and system functions such as `$clog2` are kept as they are.
+fn reference_params(module: &SvModule, expr: &str) -> String {
+    let mut result = String::new();
+    let mut chars = expr.chars().peekable();
+    while let Some(c) = chars.next() {
+        let is_word = |x: &char| x.is_ascii_alphanumeric() || *x == '_' || *x == '$';
+        if !(c.is_ascii_alphabetic() || c == '_' || c == '$' || c == '\'' || c.is_ascii_digit()) {
+            result.push(c);
+            continue;
+        }
+        let mut word = c.to_string();
+        while let Some(x) = chars.next_if(|x| is_word(x) || (c.is_ascii_digit() && *x == '\'')) {
+            word.push(x);
+        }
+        let is_ident = c.is_ascii_alphabetic() || c == '_';
+        if is_ident && module.params.iter().any(|x| x.name == word) {
+            result.push_str(format!("{}_{}", module.name, word).as_str());
+        } else {
+            result.push_str(&word);
+        }
+    }
+    result
+}
+
+impl IpxactGenerator {
+    pub fn new(vendor: String, library: String, version: String) -> Self {
+        IpxactGenerator {
+            vendor,
+            library,
+            version,
+        }
+    }
+
+    /// Returns `(file name, content)` of one IP-XACT component per module.
+    pub fn generate_components(&self, items: &[SvFile]) -> Vec<(String, String)> {
+        items
+            .iter()
+            .flat_map(|x| x.modules.iter())
+            .filter(|x| x.kind == ModuleKind::Module)
+            .map(|x| (format!("{}.xml", x.name), self.generate_component(x)))
+            .collect()
+    }
+
+    fn module_description(module: &SvModule) -> String {
+        let mut desc: Vec<String> = module.brief.iter().cloned().collect();
+        for comment in &module.comment {
+            if let CommentItem::Note(s) = comment {
+                desc.push(s.clone());
+            }
+        }
+        desc.join("\n")
+    }
+
+    /// The port as an `ipxact:port`, or `None` for a port without a known direction, such as an
+    /// interface port.
+    fn generate_port(module: &SvModule, port: &SvPort) -> Option<String> {
+        let direction = match port.direction.as_deref() {
+            Some("input") => "in",
+            Some("output") => "out",
+            Some("inout") => "inout",
+            _ => return None,
+        };
+        let mut result = String::new();
+        result.push_str("      <ipxact:port>\n");
+        result.push_str(
+            format!(
+                "        <ipxact:name>{}</ipxact:name>\n",
+                escape_xml(&port.name)
+            )
+            .as_str(),
+        );
+        if !port.comment.trim().is_empty() {
+            result.push_str(
+                format!(
+                    "        <ipxact:description>{}</ipxact:description>\n",
+                    escape_xml(port.comment.trim())
+                )
+                .as_str(),
+            );
+        }
+        result.push_str("        <ipxact:wire>\n");
+        result.push_str(
+            format!(
+                "          <ipxact:direction>{}</ipxact:direction>\n",
+                direction
+            )
+            .as_str(),
+        );
+        let ranges = port
+            .packed_dimensions()
+            .map(|x| parse_ranges(x.as_str()))
+            .unwrap_or_default();
+        if !ranges.is_empty() {
+            result.push_str("          <ipxact:vectors>\n");
+            for (left, right) in ranges {
+                result.push_str("            <ipxact:vector>\n");
+                result.push_str(
+                    format!(
+                        "              <ipxact:left>{}</ipxact:left>\n",
+                        escape_xml(&reference_params(module, &left))
+                    )
+                    .as_str(),
+                );
+                result.push_str(
+                    format!(
+                        "              <ipxact:right>{}</ipxact:right>\n",
+                        escape_xml(&reference_params(module, &right))
+                    )
+                    .as_str(),
+                );
+                result.push_str("            </ipxact:vector>\n");
+            }
+            result.push_str("          </ipxact:vectors>\n");
+        }
+        result.push_str("        </ipxact:wire>\n");
+        result.push_str("      </ipxact:port>\n");
+        Some(result)
+    }
+
+    fn generate_parameter(module: &SvModule, param: &SvParam) -> String {
+        let mut result = String::new();
+        let param_type = param
+            .param_type
+            .as_deref()
+            .filter(|x| IPXACT_TYPES.contains(x))
+            .map(|x| format!(" type=\"{}\"", x))
+            .unwrap_or_default();
+        result.push_str(
+            format!(
+                "    <ipxact:parameter parameterId=\"{}_{}\" resolve=\"user\"{}>\n",
+                escape_xml(&module.name),
+                escape_xml(&param.name),
+                param_type
+            )
+            .as_str(),
+        );
+        result.push_str(
+            format!(
+                "      <ipxact:name>{}</ipxact:name>\n",
+                escape_xml(&param.name)
+            )
+            .as_str(),
+        );
+        if !param.comment.trim().is_empty() {
+            result.push_str(
+                format!(
+                    "      <ipxact:description>{}</ipxact:description>\n",
+                    escape_xml(param.comment.trim())
+                )
+                .as_str(),
+            );
+        }
+        // `value` is required, so a parameter without a default gets an empty one
+        let default = param.default.as_deref().unwrap_or_default();
+        result.push_str(
+            format!(
+                "      <ipxact:value>{}</ipxact:value>\n",
+                escape_xml(&reference_params(module, default))
+            )
+            .as_str(),
+        );
+        result.push_str("    </ipxact:parameter>\n");
+        result
+    }
+
+    pub fn generate_component(&self, module: &SvModule) -> String {
+        let mut result = String::new();
+        result.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        result.push_str(format!("<ipxact:component xmlns:ipxact=\"{}\">\n", IPXACT_NS).as_str());
+        result.push_str(
+            format!(
+                "  <ipxact:vendor>{}</ipxact:vendor>\n",
+                escape_xml(&self.vendor)
+            )
+            .as_str(),
+        );
+        result.push_str(
+            format!(
+                "  <ipxact:library>{}</ipxact:library>\n",
+                escape_xml(&self.library)
+            )
+            .as_str(),
+        );
+        result.push_str(
+            format!(
+                "  <ipxact:name>{}</ipxact:name>\n",
+                escape_xml(&module.name)
+            )
+            .as_str(),
+        );
+        result.push_str(
+            format!(
+                "  <ipxact:version>{}</ipxact:version>\n",
+                escape_xml(&self.version)
+            )
+            .as_str(),
+        );
+        let mut ports = Vec::new();
+        for port in &module.ports {
+            match Self::generate_port(module, port) {
+                Some(x) => ports.push(x),
+                None => eprintln!(
+                    "warning: {}: port {} has no direction and is left out of the IP-XACT component",
+                    module.name, port.name
+                ),
+            }
+        }
+        if !ports.is_empty() {
+            result.push_str("  <ipxact:model>\n");
+            result.push_str("    <ipxact:ports>\n");
+            for port in ports {
+                result.push_str(port.as_str());
+            }
+            result.push_str("    </ipxact:ports>\n");
+            result.push_str("  </ipxact:model>\n");
+        }
+        let description = Self::module_description(module);
+        if !description.trim().is_empty() {
+            result.push_str(
+                format!(
+                    "  <ipxact:description>{}</ipxact:description>\n",
+                    escape_xml(description.trim())
+                )
+                .as_str(),
+            );
+        }
+        // type parameters have no value to configure
+        let params: Vec<&SvParam> = module
+            .params
+            .iter()
+            .filter(|x| x.param_type.as_deref() != Some("type"))
+            .collect();
+        if !params.is_empty() {
+            result.push_str("  <ipxact:parameters>\n");
+            for param in params {
+                result.push_str(Self::generate_parameter(module, param).as_str());
+            }
+            result.push_str("  </ipxact:parameters>\n");
+        }
+        result.push_str("</ipxact:component>\n");
+        result
+    }
+}
+
+#[test]
+fn test_generate_component_params() {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    let input = "module m #(
+    parameter WIDTH = 8,
+    parameter DEPTH = WIDTH * 2,
+    parameter N = 4,
+    parameter type T = logic
+) (
+    input [WIDTH-1:0] d, ///< data
+    output [$clog2(DEPTH)-1:0] q,
+    output [N-1:0][7:0] r,
+    input [8'hf:0] s
+);
+endmodule
+";
+    let mut file = Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    // as a parameter declared without a default
+    file.modules[0].params[2].default = None;
+    let generator = IpxactGenerator::new("v".to_string(), "l".to_string(), "1.0".to_string());
+    let xml = generator.generate_component(&file.modules[0]);
+    assert!(xml.contains(
+        "        <ipxact:name>d</ipxact:name>
+        <ipxact:description>data</ipxact:description>
+        <ipxact:wire>
+          <ipxact:direction>in</ipxact:direction>
+          <ipxact:vectors>
+            <ipxact:vector>
+              <ipxact:left>m_WIDTH-1</ipxact:left>
+              <ipxact:right>0</ipxact:right>
+"
+    ));
+    assert!(xml.contains("<ipxact:left>$clog2(m_DEPTH)-1</ipxact:left>"));
+    assert!(xml.contains("<ipxact:left>m_N-1</ipxact:left>"));
+    assert!(xml.contains("<ipxact:left>8&apos;hf</ipxact:left>"));
+    assert!(xml.contains("<ipxact:value>m_WIDTH * 2</ipxact:value>"));
+    assert!(!xml.contains("<ipxact:name>T</ipxact:name>"));
+    // an empty value for a parameter without a default
+    assert!(xml.contains(
+        "      <ipxact:name>N</ipxact:name>
+      <ipxact:value></ipxact:value>
+    </ipxact:parameter>
+"
+    ));
+}
+
+#[test]
+fn test_generate_component_directions() {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    let input = "module m (
+    input a,
+    inout b,
+    bus_if.slave s
+);
+endmodule
+";
+    let file = Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let generator = IpxactGenerator::new("v".to_string(), "l".to_string(), "1.0".to_string());
+    let xml = generator.generate_component(&file.modules[0]);
+    assert!(xml.contains("<ipxact:name>a</ipxact:name>"));
+    assert!(xml.contains(
+        "        <ipxact:name>b</ipxact:name>
+        <ipxact:wire>
+          <ipxact:direction>inout</ipxact:direction>
+"
+    ));
+    // the interface port has no direction and is left out
+    assert!(!xml.contains("<ipxact:name>s</ipxact:name>"));
+}
@@ -0,0 +1,241 @@
+use crate::{comment_parser::OpenKind, docgen::ModuleKind};
+
+/// Fixed texts used by generators, selected with `--lang`.
+pub struct Labels {
+    pub file: &'static str,
+    pub module: &'static str,
+    pub function: &'static str,
+    pub task: &'static str,
+    pub parameters: &'static str,
+    pub ports: &'static str,
+    pub author: &'static str,
+    pub example: &'static str,
+    pub note: &'static str,
+    pub reference: &'static str,
+    pub ret: &'static str,
+    pub waveform: &'static str,
+    pub state_machine: &'static str,
+    pub name: &'static str,
+    pub default: &'static str,
+    pub type_: &'static str,
+    pub dimensions: &'static str,
+    pub direction: &'static str,
+    pub brief: &'static str,
+    pub include_graph: &'static str,
+    pub includes: &'static str,
+    pub port: &'static str,
+    pub parameter: &'static str,
+    pub deprecated: &'static str,
+    pub since: &'static str,
+    pub version: &'static str,
+    pub reason: &'static str,
+    pub deprecated_items: &'static str,
+    pub version_history: &'static str,
+    pub added_in: &'static str,
+    pub todo: &'static str,
+    pub bug: &'static str,
+    pub fixme: &'static str,
+    pub open_items: &'static str,
+    pub location: &'static str,
+    pub interface: &'static str,
+    pub package: &'static str,
+    pub ungrouped: &'static str,
+    pub types: &'static str,
+    pub definition: &'static str,
+    pub unresolved: &'static str,
+    pub registers: &'static str,
+    pub offset: &'static str,
+    pub access: &'static str,
+    pub reset: &'static str,
+    pub bits: &'static str,
+    pub bitfield: &'static str,
+    pub inherited: &'static str,
+    pub master: &'static str,
+    pub slave: &'static str,
+    pub domain: &'static str,
+    pub clock: &'static str,
+    pub reset_signal: &'static str,
+    pub resets: &'static str,
+    pub synchronous_ports: &'static str,
+    pub clock_domains: &'static str,
+    pub active_low: &'static str,
+    pub active_high: &'static str,
+    pub asynchronous: &'static str,
+    pub synchronous: &'static str,
+    pub traceability: &'static str,
+    pub requirement: &'static str,
+    pub requirements: &'static str,
+    pub entities: &'static str,
+    pub requirements_to_entities: &'static str,
+    pub entities_to_requirements: &'static str,
+    pub not_covered: &'static str,
+    pub transition: &'static str,
+}
+
+const EN: Labels = Labels {
+    file: "File",
+    module: "module",
+    function: "function",
+    task: "task",
+    parameters: "Parameters",
+    ports: "Ports",
+    author: "Author",
+    example: "Example",
+    note: "Note",
+    reference: "Ref",
+    ret: "Return",
+    waveform: "Waveform",
+    state_machine: "State Machine",
+    name: "name",
+    default: "default",
+    type_: "type",
+    dimensions: "dimensions",
+    direction: "direction",
+    brief: "brief",
+    include_graph: "Include graph",
+    includes: "includes",
+    port: "port",
+    parameter: "parameter",
+    deprecated: "Deprecated",
+    since: "Since",
+    version: "Version",
+    reason: "reason",
+    deprecated_items: "Deprecated items",
+    version_history: "Version history",
+    added_in: "Added in version",
+    todo: "TODO",
+    bug: "BUG",
+    fixme: "FIXME",
+    open_items: "Open items",
+    location: "location",
+    interface: "interface",
+    package: "package",
+    ungrouped: "Ungrouped",
+    types: "Types",
+    definition: "definition",
+    unresolved: "unresolved",
+    registers: "Registers",
+    offset: "offset",
+    access: "access",
+    reset: "reset",
+    bits: "bits",
+    bitfield: "Bitfield",
+    inherited: "inherited from",
+    master: "master",
+    slave: "slave",
+    domain: "domain",
+    clock: "clock",
+    reset_signal: "reset",
+    resets: "resets",
+    synchronous_ports: "synchronous ports",
+    clock_domains: "Clock domains",
+    active_low: "active-low",
+    active_high: "active-high",
+    asynchronous: "async",
+    synchronous: "sync",
+    traceability: "Traceability",
+    requirement: "requirement",
+    requirements: "requirements",
+    entities: "design elements",
+    requirements_to_entities: "Requirements",
+    entities_to_requirements: "Design elements",
+    not_covered: "not covered",
+    transition: "transition",
+};
+
+const ZH: Labels = Labels {
+    file: "文件",
+    module: "模块",
+    function: "函数",
+    task: "任务",
+    parameters: "参数",
+    ports: "端口",
+    author: "作者",
+    example: "示例",
+    note: "注意",
+    reference: "参考",
+    ret: "返回值",
+    waveform: "波形",
+    state_machine: "状态机",
+    name: "名称",
+    default: "默认值",
+    type_: "类型",
+    dimensions: "维度",
+    direction: "方向",
+    brief: "说明",
+    include_graph: "文件包含关系",
+    includes: "包含",
+    port: "端口",
+    parameter: "参数",
+    deprecated: "已弃用",
+    since: "起始版本",
+    version: "版本",
+    reason: "原因",
+    deprecated_items: "已弃用项",
+    version_history: "版本历史",
+    added_in: "新增于版本",
+    todo: "待办",
+    bug: "缺陷",
+    fixme: "待修复",
+    open_items: "待处理事项",
+    location: "位置",
+    interface: "接口",
+    package: "包",
+    ungrouped: "未分组",
+    types: "类型定义",
+    definition: "定义",
+    unresolved: "未解析",
+    registers: "寄存器",
+    offset: "偏移",
+    access: "访问",
+    reset: "复位值",
+    bits: "位",
+    bitfield: "位域图",
+    inherited: "继承自",
+    master: "主设备",
+    slave: "从设备",
+    domain: "时钟域",
+    clock: "时钟",
+    reset_signal: "复位",
+    resets: "复位",
+    synchronous_ports: "同步端口",
+    clock_domains: "时钟域",
+    active_low: "低有效",
+    active_high: "高有效",
+    asynchronous: "异步",
+    synchronous: "同步",
+    traceability: "需求追溯",
+    requirement: "需求",
+    requirements: "需求",
+    entities: "设计元素",
+    requirements_to_entities: "需求",
+    entities_to_requirements: "设计元素",
+    not_covered: "未覆盖",
+    transition: "状态转移",
+};
+
+impl Labels {
+    pub fn module_kind(&self, kind: ModuleKind) -> &'static str {
+        match kind {
+            ModuleKind::Module => self.module,
+            ModuleKind::Interface => self.interface,
+            ModuleKind::Package => self.package,
+        }
+    }
+
+    pub fn open_kind(&self, kind: OpenKind) -> &'static str {
+        match kind {
+            OpenKind::Todo => self.todo,
+            OpenKind::Bug => self.bug,
+            OpenKind::Fixme => self.fixme,
+        }
+    }
+
+    /// Labels for `lang`, falling back to English for unknown languages.
+    pub fn for_lang(lang: Option<&str>) -> &'static Labels {
+        match lang.map(|x| x.to_lowercase()).as_deref() {
+            Some("zh") | Some("cn") | Some("zh-cn") | Some("zh_cn") => &ZH,
+            _ => &EN,
+        }
+    }
+}
@@ -1,13 +1,31 @@
+use config::Config;
+use copydoc::resolve_copydoc;
+use csv::{parse_requirements, requirement_warnings, OpenItemsCsvGenerator, TraceCsvGenerator};
 use docgen::Docgen;
+use encoding_rs::Encoding;
+use examples::check_examples;
 use generator::{DocgenGenerator, MarkdownGenerator};
+use inherit::inherit_port_comments;
+use ipxact::IpxactGenerator;
 use std::{collections::HashMap, fs, path::PathBuf};
 use structopt::StructOpt;
 use sv_parser::{Define, DefineText};
+use testbench::TestbenchGenerator;
 
+pub mod bus;
 pub mod comment_parser;
+pub mod config;
+pub mod copydoc;
+pub mod csv;
 pub mod docgen;
+pub mod examples;
 pub mod generator;
+pub mod inherit;
+pub mod ipxact;
+pub mod locale;
 pub mod numbered_list;
+pub mod symbols;
+pub mod testbench;
 
 #[derive(StructOpt)]
 struct Opt {
@@ -33,6 +51,72 @@ struct Opt {
 
     #[structopt(long = "graphviz")]
     pub graphviz: Option<String>,
+
+    /// PlantUML command rendering `@plantuml` diagrams
+    #[structopt(long = "plantuml")]
+    pub plantuml: Option<String>,
+
+    /// Language of the documentation, selects `@cmd[lang]` variants and output labels
+    #[structopt(long = "lang")]
+    pub lang: Option<String>,
+
+    /// Encoding of the source files, such as `gbk` or `latin1`; detected automatically by default
+    #[structopt(long = "encoding")]
+    pub encoding: Option<String>,
+
+    /// Append a section listing which files include which
+    #[structopt(long = "include-graph")]
+    pub include_graph: bool,
+
+    /// Take the first sentence of the documentation as brief and the rest as details
+    #[structopt(long = "autobrief")]
+    pub autobrief: bool,
+
+    /// Configuration file declaring custom tags
+    #[structopt(short = "c", long = "config")]
+    pub config: Option<String>,
+
+    /// Emit a testbench skeleton for the given module instead of documentation
+    #[structopt(long = "emit-tb")]
+    pub emit_tb: Option<String>,
+
+    /// Write one IP-XACT component file per module into the given directory
+    #[structopt(long = "ipxact")]
+    pub ipxact: Option<PathBuf>,
+
+    #[structopt(long = "ipxact-vendor", default_value = "user")]
+    pub ipxact_vendor: String,
+
+    #[structopt(long = "ipxact-library", default_value = "lib")]
+    pub ipxact_library: String,
+
+    #[structopt(long = "ipxact-version", default_value = "1.0")]
+    pub ipxact_version: String,
+
+    /// Also write the @todo, @bug and @fixme items as CSV to the given file
+    #[structopt(long = "open-items")]
+    pub open_items: Option<PathBuf>,
+
+    /// Requirements CSV (id, title) whose coverage the traceability matrix reports
+    #[structopt(long = "requirements")]
+    pub requirements: Option<PathBuf>,
+
+    /// Also write the @req traceability matrix as CSV to the given file
+    #[structopt(long = "trace")]
+    pub trace: Option<PathBuf>,
+
+    /// Check that the @example snippets of modules parse and instantiate existing ports and
+    /// parameters instead of generating documentation
+    #[structopt(long = "check-examples")]
+    pub check_examples: bool,
+}
+
+/// Exits with status 1 when a source file could not be read or parsed or refers to a missing
+/// image, once the output is written.
+fn exit_on_failure(failed: bool) {
+    if failed {
+        std::process::exit(1);
+    }
 }
 
 fn main() {
@@ -52,10 +136,108 @@ fn main() {
         defines.insert(ident, Some(define));
     }
 
+    let encoding = opt.encoding.as_ref().map(|x| {
+        Encoding::for_label(x.as_bytes()).unwrap_or_else(|| {
+            eprintln!("unknown encoding {}", x);
+            std::process::exit(1);
+        })
+    });
+
+    let config = match &opt.config {
+        Some(file) => Config::from_file(file).unwrap_or_else(|e| {
+            eprintln!("unable to load config {}: {}", file, e);
+            std::process::exit(1);
+        }),
+        None => Config::default(),
+    };
+
+    let requirements = match &opt.requirements {
+        Some(file) => match fs::read_to_string(file) {
+            Ok(x) => parse_requirements(&x),
+            Err(e) => {
+                eprintln!("unable to load requirements {}: {}", file.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => Vec::new(),
+    };
+
     let mut result = vec![];
+    let mut failed = false;
     for file in opt.files {
-        let docgen = Docgen::from_file(file.to_str().unwrap(), &defines, &opt.includes).unwrap();
-        result.push(docgen.parse_tree());
+        match Docgen::from_file(file.to_str().unwrap(), &defines, &opt.includes, encoding) {
+            Ok(docgen) => {
+                let parsed = docgen
+                    .with_lang(opt.lang.clone())
+                    .with_tags(config.tags.clone())
+                    .with_autobrief(opt.autobrief)
+                    .parse_tree();
+                failed |= !parsed.missing_images.is_empty();
+                result.push(parsed);
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                failed = true;
+            }
+        }
+    }
+    resolve_copydoc(&mut result);
+    inherit_port_comments(&mut result);
+
+    if opt.check_examples {
+        let passed = check_examples(&result, &defines, &opt.includes);
+        exit_on_failure(failed || !passed);
+        return;
+    }
+
+    if opt.requirements.is_some() {
+        let traces: Vec<_> = result.iter().flat_map(|x| x.traces()).collect();
+        for warning in requirement_warnings(&requirements, &traces) {
+            eprintln!("warning: {}", warning);
+        }
+    }
+    if let Some(csv) = &opt.trace {
+        let trace_gen = TraceCsvGenerator {
+            requirements: requirements.clone(),
+        };
+        fs::write(csv, trace_gen.generate(result.clone())).unwrap();
+    }
+
+    if let Some(csv) = &opt.open_items {
+        fs::write(csv, OpenItemsCsvGenerator.generate(result.clone())).unwrap();
+    }
+
+    if let Some(module) = &opt.emit_tb {
+        if !result
+            .iter()
+            .any(|x| x.modules.iter().any(|y| &y.name == module))
+        {
+            eprintln!("module {} not found", module);
+            std::process::exit(1);
+        }
+        let tb_gen = TestbenchGenerator::new(module.clone());
+        let tb_str = tb_gen.generate(result);
+        if let Some(output) = &opt.output {
+            fs::write(output, tb_str).unwrap();
+        } else {
+            println!("{}", tb_str);
+        }
+        exit_on_failure(failed);
+        return;
+    }
+
+    if let Some(dir) = &opt.ipxact {
+        fs::create_dir_all(dir).unwrap();
+        let ipxact_gen = IpxactGenerator::new(
+            opt.ipxact_vendor.clone(),
+            opt.ipxact_library.clone(),
+            opt.ipxact_version.clone(),
+        );
+        for (name, content) in ipxact_gen.generate_components(&result) {
+            fs::write(dir.join(name), content).unwrap();
+        }
+        exit_on_failure(failed);
+        return;
     }
 
     let cwd = if opt.output.is_none() {
@@ -68,11 +250,21 @@ fn main() {
         // x.unwrap().to_str().unwrap()
     };
 
-    let md_gen = MarkdownGenerator::new(cwd.to_string(), opt.wavedrom, opt.graphviz);
+    let mut md_gen = MarkdownGenerator::new(
+        cwd.to_string(),
+        opt.wavedrom,
+        opt.graphviz,
+        opt.lang.as_deref(),
+    );
+    md_gen.include_graph = opt.include_graph;
+    md_gen.plantuml = opt.plantuml;
+    md_gen.config = config;
+    md_gen.requirements = requirements;
     let md_str = md_gen.generate(result);
     if let Some(output) = &opt.output {
         fs::write(output, md_str).unwrap();
     } else {
         println!("{}", md_str);
     }
+    exit_on_failure(failed);
 }
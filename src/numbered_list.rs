@@ -5,6 +5,12 @@ pub struct NumberedList {
     sep: String,
 }
 
+impl Default for NumberedList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NumberedList {
     pub fn new() -> Self {
         NumberedList {
@@ -0,0 +1,345 @@
+use std::collections::HashSet;
+
+use crate::{
+    comment_parser::CommentItem,
+    docgen::{ModuleKind, SvFile, SvFunctionTask, SvModule},
+};
+
+/// A documented entity that references can point to.
+pub struct Symbol {
+    pub module: String,
+    /// Function/task, port, param, typedef, port group or register of the module, `None` for
+    /// the module itself.
+    pub member: Option<String>,
+    pub anchor: String,
+}
+
+/// Resolves `@see`, `@ref` and `{@link ...}` targets to anchors of the documentation.
+#[derive(Default)]
+pub struct Symbols {
+    symbols: Vec<Symbol>,
+    /// `(module, instance, instantiated module)`
+    instances: Vec<(String, String, String)>,
+    used: HashSet<String>,
+    /// Emit the anchors of all entities, not only of the referenced ones.
+    pub all_anchors: bool,
+}
+
+pub fn module_anchor(module: &SvModule) -> String {
+    let kind = match module.kind {
+        ModuleKind::Module => "module",
+        ModuleKind::Interface => "interface",
+        ModuleKind::Package => "package",
+    };
+    format!("{}-{}", kind, module.name)
+}
+
+pub fn task_anchor(module: &SvModule, task: &SvFunctionTask) -> String {
+    let kind = if task.is_function { "function" } else { "task" };
+    format!("{}-{}-{}", kind, module.name, task.name)
+}
+
+pub fn port_anchor(module: &str, port: &str) -> String {
+    format!("port-{}-{}", module, port)
+}
+
+pub fn param_anchor(module: &str, param: &str) -> String {
+    format!("param-{}-{}", module, param)
+}
+
+pub fn type_anchor(module: &str, typedef: &str) -> String {
+    format!("type-{}-{}", module, typedef)
+}
+
+pub fn port_group_anchor(module: &str, group: &str) -> String {
+    format!("portgroup-{}-{}", module, group)
+}
+
+pub fn register_anchor(module: &str, register: &str) -> String {
+    format!("reg-{}-{}", module, register)
+}
+
+pub fn is_url(target: &str) -> bool {
+    target.contains("://") || target.starts_with("mailto:") || target.starts_with("www.")
+}
+
+/// `{@link target}` and `{@link target text}` in `text`, as `(start, end, target, text)`.
+pub fn inline_links(text: &str) -> Vec<(usize, usize, &str, Option<&str>)> {
+    let mut result = Vec::new();
+    let mut from = 0;
+    while let Some(start) = text[from..].find("{@link").map(|x| x + from) {
+        let end = match text[start..].find('}') {
+            Some(x) => start + x + 1,
+            None => break,
+        };
+        let inner = text[start + "{@link".len()..end - 1].trim();
+        let (target, label) = match inner.split_once(char::is_whitespace) {
+            Some((target, label)) => (target, Some(label.trim())),
+            None => (inner, None),
+        };
+        if !target.is_empty() {
+            result.push((start, end, target, label));
+        }
+        from = end;
+    }
+    result
+}
+
+/// Targets referenced by a comment item.
+fn item_targets(item: &CommentItem) -> Vec<&str> {
+    let text = match item {
+        CommentItem::See(x) | CommentItem::Ref(x) => {
+            return x.split_whitespace().next().into_iter().collect();
+        }
+        CommentItem::Plain(x)
+        | CommentItem::Brief(x)
+        | CommentItem::Details(x)
+        | CommentItem::Note(x)
+        | CommentItem::Return(x)
+        | CommentItem::Author(x) => x,
+        CommentItem::Port { desc, .. }
+        | CommentItem::Param { desc, .. }
+        | CommentItem::Custom { desc, .. }
+        | CommentItem::Deprecated { desc, .. } => desc,
+        CommentItem::Open { text, .. } => text,
+        CommentItem::Lang { item, .. } => return item_targets(item),
+        _ => return Vec::new(),
+    };
+    inline_links(text).into_iter().map(|x| x.2).collect()
+}
+
+impl Symbols {
+    pub fn new(items: &[SvFile]) -> Self {
+        let mut result = Symbols::default();
+        let modules: Vec<&SvModule> = items.iter().flat_map(|x| x.modules.iter()).collect();
+        for module in &modules {
+            let name = &module.name;
+            let member = |member: &str, anchor: String| Symbol {
+                module: name.clone(),
+                member: Some(member.to_string()),
+                anchor,
+            };
+            result.symbols.push(Symbol {
+                module: name.clone(),
+                member: None,
+                anchor: module_anchor(module),
+            });
+            for task in &module.tasks {
+                result
+                    .symbols
+                    .push(member(&task.name, task_anchor(module, task)));
+            }
+            for port in &module.ports {
+                result
+                    .symbols
+                    .push(member(&port.name, port_anchor(name, &port.name)));
+            }
+            for param in &module.params {
+                result
+                    .symbols
+                    .push(member(&param.name, param_anchor(name, &param.name)));
+            }
+            for typedef in &module.typedefs {
+                result
+                    .symbols
+                    .push(member(&typedef.name, type_anchor(name, &typedef.name)));
+            }
+            for group in &module.port_groups {
+                result
+                    .symbols
+                    .push(member(&group.name, port_group_anchor(name, &group.name)));
+            }
+            for register in &module.registers {
+                result.symbols.push(member(
+                    &register.name,
+                    register_anchor(name, &register.name),
+                ));
+            }
+            for instance in &module.instances {
+                result.instances.push((
+                    name.clone(),
+                    instance.name.clone(),
+                    instance.module.clone(),
+                ));
+            }
+        }
+
+        // only referenced ports, params, typedefs and registers get an anchor
+        let mut used = HashSet::new();
+        for module in &modules {
+            let mut targets: Vec<&str> = Vec::new();
+            let comments = std::iter::once(&module.comment)
+                .chain(module.tasks.iter().map(|x| &x.comment))
+                .flatten();
+            for item in comments {
+                targets.extend(item_targets(item));
+            }
+            targets.extend(module.ports.iter().filter_map(|x| x.inherited.as_deref()));
+            let texts = module
+                .brief
+                .iter()
+                .chain(module.tasks.iter().filter_map(|x| x.brief.as_ref()))
+                .chain(
+                    module
+                        .tasks
+                        .iter()
+                        .flat_map(|x| x.ports.iter().map(|x| &x.comment)),
+                )
+                .chain(
+                    module
+                        .tasks
+                        .iter()
+                        .flat_map(|x| x.params.iter().map(|x| &x.comment)),
+                )
+                .chain(module.ports.iter().map(|x| &x.comment))
+                .chain(module.params.iter().map(|x| &x.comment))
+                .chain(module.typedefs.iter().map(|x| &x.comment))
+                .chain(module.registers.iter().map(|x| &x.desc))
+                .chain(
+                    module
+                        .registers
+                        .iter()
+                        .flat_map(|x| x.fields.iter().map(|x| &x.desc)),
+                );
+            for text in texts {
+                targets.extend(inline_links(text).into_iter().map(|x| x.2));
+            }
+            for target in targets {
+                if let Some(symbol) = result.resolve(target, Some(&module.name)) {
+                    used.insert(symbol.anchor.clone());
+                }
+            }
+        }
+        for group in items.iter().flat_map(|x| x.groups.iter()) {
+            let mut targets: Vec<&str> = group.comment.iter().flat_map(item_targets).collect();
+            if let Some(brief) = &group.brief {
+                targets.extend(inline_links(brief).into_iter().map(|x| x.2));
+            }
+            for target in targets {
+                if let Some(symbol) = result.resolve(target, None) {
+                    used.insert(symbol.anchor.clone());
+                }
+            }
+        }
+        result.used = used;
+        result
+    }
+
+    fn find(&self, module: &str, member: Option<&str>) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .find(|x| x.module == module && x.member.as_deref() == member)
+    }
+
+    /// Symbol referenced by `target` from the documentation of the module `scope`.
+    ///
+    /// `target` is a module, `module.member`, `package::member`, `instance.port` or
+    /// a member of `scope`; other members must be unique among all modules.
+    pub fn resolve(&self, target: &str, scope: Option<&str>) -> Option<&Symbol> {
+        let parts: Vec<&str> = target
+            .split("::")
+            .flat_map(|x| x.split('.'))
+            .map(|x| x.trim_end_matches("()"))
+            .collect();
+        match parts.as_slice() {
+            [name] => self
+                .find(name, None)
+                .or_else(|| scope.and_then(|x| self.find(x, Some(name))))
+                .or_else(|| {
+                    let mut found = self
+                        .symbols
+                        .iter()
+                        .filter(|x| x.member.as_deref() == Some(*name));
+                    match (found.next(), found.next()) {
+                        (Some(x), None) => Some(x),
+                        _ => None,
+                    }
+                }),
+            [parent, member] => {
+                let instance = |in_scope: bool| {
+                    self.instances
+                        .iter()
+                        .filter(|(module, instance, _)| {
+                            instance == parent && (!in_scope || scope == Some(module.as_str()))
+                        })
+                        .find_map(|(_, _, module)| self.find(module, Some(member)))
+                };
+                // instances of the documented module first, then of any module
+                self.find(parent, Some(member))
+                    .or_else(|| instance(true))
+                    .or_else(|| instance(false))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the anchor of an entity is emitted.
+    pub fn is_anchored(&self, anchor: &str) -> bool {
+        self.all_anchors || self.used.contains(anchor)
+    }
+}
+
+#[test]
+fn test_inline_links() {
+    assert_eq!(
+        inline_links("uses {@link pkg::state_t} and {@link u_core.clk the clock}."),
+        vec![
+            (5, 25, "pkg::state_t", None),
+            (30, 58, "u_core.clk", Some("the clock")),
+        ]
+    );
+}
+
+#[test]
+fn test_resolve() {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    let input = "package pkg;
+    typedef logic [1:0] state_t;
+    function automatic int f (
+        input int a ///< see {@link core.d}
+    );
+    endfunction
+endpackage
+module core (input clk, input d, input en);
+endmodule
+module top (input clk, input d);
+    core u_core (.clk(clk), .d(d), .en(1'b1));
+endmodule
+";
+    let file = Docgen::new(input, "m.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let symbols = Symbols::new(&[file]);
+    let anchor = |target: &str, scope: Option<&str>| {
+        symbols.resolve(target, scope).map(|x| x.anchor.clone())
+    };
+    assert_eq!(anchor("core", None), Some("module-core".to_string()));
+    assert_eq!(anchor("core.clk", None), Some(port_anchor("core", "clk")));
+    assert_eq!(
+        anchor("pkg::state_t", None),
+        Some(type_anchor("pkg", "state_t"))
+    );
+    assert_eq!(anchor("pkg::f()", None), Some("function-pkg-f".to_string()));
+    // an instance stands for its module
+    assert_eq!(
+        anchor("u_core.en", Some("top")),
+        Some(port_anchor("core", "en"))
+    );
+    assert_eq!(anchor("u_core.en", None), Some(port_anchor("core", "en")));
+    // a member of the documented module comes first
+    assert_eq!(anchor("clk", Some("top")), Some(port_anchor("top", "clk")));
+    assert_eq!(
+        anchor("clk", Some("core")),
+        Some(port_anchor("core", "clk"))
+    );
+    // a member of several modules is ambiguous outside of them, a unique one is not
+    assert_eq!(anchor("clk", None), None);
+    assert_eq!(anchor("clk", Some("pkg")), None);
+    assert_eq!(anchor("en", None), Some(port_anchor("core", "en")));
+    assert_eq!(anchor("core.missing", None), None);
+    // linked from the comment of a function port
+    assert!(symbols.is_anchored(&port_anchor("core", "d")));
+    assert!(!symbols.is_anchored(&port_anchor("top", "d")));
+}
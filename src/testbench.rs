@@ -0,0 +1,349 @@
+use crate::{
+    comment_parser::CommentItem,
+    docgen::{SvFile, SvModule, SvPort},
+    generator::DocgenGenerator,
+};
+
+pub struct TestbenchGenerator {
+    pub module: String,
+    pub clock_period: u32,
+    pub reset_cycles: u32,
+}
+
+impl TestbenchGenerator {
+    pub fn new(module: String) -> Self {
+        TestbenchGenerator {
+            module,
+            clock_period: 10,
+            reset_cycles: 10,
+        }
+    }
+
+    fn is_input(port: &SvPort) -> bool {
+        port.direction.as_deref() == Some("input")
+    }
+
+    /// Lowercase parts of the port name separated by `_`, e.g. `rst`, `n` for `RST_N`.
+    fn words(port: &SvPort) -> Vec<String> {
+        port.name
+            .to_lowercase()
+            .split('_')
+            .map(String::from)
+            .collect()
+    }
+
+    /// Whether `port` is a clock input, given by `@clock` or inferred from `always_ff`
+    /// blocks, or named like a clock if its clocking is unknown.
+    fn is_clock(port: &SvPort) -> bool {
+        if !port.clocking.is_empty() {
+            return Self::is_input(port) && port.clocking.clock;
+        }
+        Self::is_input(port)
+            && Self::words(port)
+                .iter()
+                .any(|x| x == "clock" || x.ends_with("clk"))
+    }
+
+    /// Whether `port` is a reset input, given by `@reset` or inferred from `always_ff`
+    /// blocks, or named like a reset if its clocking is unknown.
+    fn is_reset(port: &SvPort) -> bool {
+        if !port.clocking.is_empty() {
+            return Self::is_input(port) && port.clocking.reset.is_some();
+        }
+        Self::is_input(port)
+            && Self::words(port).iter().any(|x| {
+                matches!(
+                    x.as_str(),
+                    "rst" | "rstn" | "nrst" | "reset" | "resetn" | "nreset" | "areset" | "aresetn"
+                )
+            })
+    }
+
+    /// Whether the reset `port` is active-low, given by `@reset` or inferred, or named like
+    /// an active-low reset otherwise.
+    fn is_active_low(port: &SvPort) -> bool {
+        if let Some(active_low) = port.clocking.reset.and_then(|x| x.active_low) {
+            return active_low;
+        }
+        Self::is_reset(port)
+            && Self::words(port).iter().any(|x| {
+                matches!(
+                    x.as_str(),
+                    "n" | "ni" | "b" | "rstn" | "nrst" | "resetn" | "nreset" | "aresetn"
+                )
+            })
+    }
+
+    fn declare_port(port: &SvPort) -> String {
+        let net = if port.direction.as_deref() == Some("inout") {
+            "wire"
+        } else {
+            "logic"
+        };
+        let mut decl = String::from(net);
+        if let Some(packed) = port.packed_dimensions() {
+            decl.push(' ');
+            decl.push_str(packed.as_str());
+        }
+        decl.push(' ');
+        decl.push_str(port.name.as_str());
+        if let Some(unpacked) = port.dimensions.as_ref().filter(|x| !x.is_empty()) {
+            decl.push(' ');
+            decl.push_str(unpacked.as_str());
+        }
+        format!("    {};\n", decl)
+    }
+
+    fn generate_module(&self, module: &SvModule) -> String {
+        let mut result = String::new();
+        let clocks: Vec<&SvPort> = module.ports.iter().filter(|x| Self::is_clock(x)).collect();
+        let resets: Vec<&SvPort> = module.ports.iter().filter(|x| Self::is_reset(x)).collect();
+
+        result.push_str("`timescale 1ns/1ps\n\n");
+        result.push_str(format!("module tb_{};\n\n", module.name).as_str());
+
+        if !module.params.is_empty() {
+            result.push_str("    // Parameters\n");
+            for param in &module.params {
+                let param_type = param
+                    .param_type
+                    .as_ref()
+                    .map(|x| format!("{} ", x))
+                    .unwrap_or_default();
+                let is_type = param.param_type.as_deref() == Some("type");
+                match (&param.default, is_type) {
+                    (Some(default), _) => result.push_str(
+                        format!(
+                            "    localparam {}{} = {};\n",
+                            param_type, param.name, default
+                        )
+                        .as_str(),
+                    ),
+                    (None, true) => result.push_str(
+                        format!(
+                            "    localparam type {} = logic; // TODO: no default type\n",
+                            param.name
+                        )
+                        .as_str(),
+                    ),
+                    // left for the user to fill in rather than guessing a value
+                    (None, false) => result.push_str(
+                        format!(
+                            "    // TODO: no default value\n    // localparam {}{} = ;\n",
+                            param_type, param.name
+                        )
+                        .as_str(),
+                    ),
+                }
+            }
+            result.push('\n');
+        }
+
+        if !module.ports.is_empty() {
+            result.push_str("    // Ports\n");
+            for port in &module.ports {
+                result.push_str(Self::declare_port(port).as_str());
+            }
+            result.push('\n');
+        }
+
+        for clock in &clocks {
+            result.push_str(format!("    // Clock {}\n", clock.name).as_str());
+            result.push_str(format!("    initial {} = 1'b0;\n", clock.name).as_str());
+            result.push_str(
+                format!(
+                    "    always #{} {} = ~{};\n\n",
+                    self.clock_period / 2,
+                    clock.name,
+                    clock.name
+                )
+                .as_str(),
+            );
+        }
+
+        for reset in &resets {
+            let (active, inactive) = if Self::is_active_low(reset) {
+                ("1'b0", "1'b1")
+            } else {
+                ("1'b1", "1'b0")
+            };
+            result.push_str(format!("    // Reset {}\n", reset.name).as_str());
+            result.push_str("    initial begin\n");
+            result.push_str(format!("        {} = {};\n", reset.name, active).as_str());
+            // a reset is released on its own clock domain if known
+            let clock = clocks
+                .iter()
+                .find(|x| reset.clocking.domain.as_ref() == Some(&x.name))
+                .or(clocks.first());
+            if let Some(clock) = clock {
+                result.push_str(
+                    format!(
+                        "        repeat ({}) @(posedge {});\n",
+                        self.reset_cycles, clock.name
+                    )
+                    .as_str(),
+                );
+            } else {
+                result.push_str(
+                    format!("        #{};\n", self.reset_cycles * self.clock_period).as_str(),
+                );
+            }
+            result.push_str(format!("        {} = {};\n", reset.name, inactive).as_str());
+            result.push_str("    end\n\n");
+        }
+
+        result.push_str("    // DUT\n");
+        result.push_str(format!("    {}", module.name).as_str());
+        if !module.params.is_empty() {
+            let params: Vec<String> = module
+                .params
+                .iter()
+                .map(|x| format!("        .{}({})", x.name, x.name))
+                .collect();
+            result.push_str(format!(" #(\n{}\n    )", params.join(",\n")).as_str());
+        }
+        let ports: Vec<String> = module
+            .ports
+            .iter()
+            .map(|x| format!("        .{}({})", x.name, x.name))
+            .collect();
+        if ports.is_empty() {
+            result.push_str(" u_dut ();\n\n");
+        } else {
+            result.push_str(format!(" u_dut (\n{}\n    );\n\n", ports.join(",\n")).as_str());
+        }
+
+        for comment in &module.comment {
+            if let CommentItem::Example { code, .. } = comment {
+                result.push_str("    // Example from module documentation:\n");
+                for line in code.lines() {
+                    result.push_str(format!("    // {}\n", line).trim_end());
+                    result.push('\n');
+                }
+                result.push('\n');
+            }
+        }
+
+        result.push_str("    initial begin\n");
+        for reset in &resets {
+            let inactive = if Self::is_active_low(reset) {
+                "1'b1"
+            } else {
+                "1'b0"
+            };
+            result.push_str(format!("        wait ({} === {});\n", reset.name, inactive).as_str());
+        }
+        if let Some(clock) = clocks.first() {
+            result.push_str(
+                format!(
+                    "        repeat ({}) @(posedge {});\n",
+                    self.reset_cycles, clock.name
+                )
+                .as_str(),
+            );
+        }
+        result.push_str("        // TODO: stimulus\n");
+        result.push_str("        $finish;\n");
+        result.push_str("    end\n\n");
+        result.push_str("endmodule\n");
+        result
+    }
+}
+
+impl DocgenGenerator for TestbenchGenerator {
+    fn generate(&self, items: Vec<SvFile>) -> String {
+        items
+            .iter()
+            .flat_map(|x| x.modules.iter())
+            .find(|x| x.name == self.module)
+            .map(|x| self.generate_module(x))
+            .unwrap_or_default()
+    }
+}
+
+#[test]
+fn test_generate_module() {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    let input = "module dut #(parameter W = 8) (
+    input clk_i,
+    input rst_ni,
+    input [W-1:0] preset_value,
+    output [W-1:0] q
+);
+endmodule
+";
+    let file = Docgen::new(input, "dut.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let tb = TestbenchGenerator::new("dut".to_string()).generate(vec![file]);
+    assert!(tb.contains("    initial clk_i = 1'b0;\n    always #5 clk_i = ~clk_i;\n"));
+    assert!(tb.contains(
+        "        rst_ni = 1'b0;\n        repeat (10) @(posedge clk_i);\n        rst_ni = 1'b1;\n"
+    ));
+    // `preset_value` is not a reset
+    assert!(!tb.contains("preset_value = "));
+    assert!(tb.contains("    dut #(\n        .W(W)\n    ) u_dut (\n        .clk_i(clk_i),\n"));
+    // the stimulus starts after the reset is released
+    assert!(tb.contains("        wait (rst_ni === 1'b1);\n"));
+}
+
+#[test]
+fn test_generate_module_clocking() {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    let input = "module dut (
+    input sys_clk, ///< @clock
+    input wclk,
+    input rst, ///< @reset active-low async
+    input rst_n, ///< @domain wclk
+    output logic q
+);
+    always_ff @(posedge wclk or posedge arst) q <= 1'b0;
+endmodule
+";
+    let file = Docgen::new(input, "dut.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let tb = TestbenchGenerator::new("dut".to_string()).generate(vec![file]);
+    assert!(tb.contains("    always #5 sys_clk = ~sys_clk;\n"));
+    // inferred from the `always_ff` block
+    assert!(tb.contains("    always #5 wclk = ~wclk;\n"));
+    // the polarity of `@reset` overrides the name
+    assert!(tb.contains("        rst = 1'b0;\n        repeat (10) @(posedge sys_clk);\n"));
+    // a port with clocking is not guessed from its name
+    assert!(!tb.contains("        rst_n = "));
+}
+
+#[test]
+fn test_generate_module_params() {
+    use crate::docgen::Docgen;
+    use std::{collections::HashMap, path::PathBuf};
+
+    let input = "module dut #(
+    parameter W = 8,
+    parameter int N,
+    parameter type T = logic [3:0],
+    parameter type U
+) (
+    input T a,
+    input b_n,
+    input rst_b
+);
+endmodule
+";
+    let file = Docgen::new(input, "dut.sv", &HashMap::new(), &Vec::<PathBuf>::new())
+        .unwrap()
+        .parse_tree();
+    let tb = TestbenchGenerator::new("dut".to_string()).generate(vec![file]);
+    assert!(tb.contains("    localparam W = 8;\n"));
+    // a value is not guessed
+    assert!(tb.contains("    // TODO: no default value\n    // localparam int N = ;\n"));
+    assert!(tb.contains("    localparam type T = logic [3:0];\n"));
+    assert!(tb.contains("    localparam type U = logic; // TODO: no default type\n"));
+    // `_b` marks an active-low reset, but `b_n` is no reset
+    assert!(tb.contains("        rst_b = 1'b0;\n"));
+    assert!(!tb.contains("        b_n = "));
+}